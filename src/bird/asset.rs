@@ -1,79 +1,118 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
-use bevy::prelude::*;
+use bevy::{ecs::query::QueryItem, prelude::*};
 use serde::Deserialize;
 
-use super::{Bird, BirdHungerBar};
+use super::{
+    Bird, BirdHungerBar,
+    avoidance::Avoidance,
+    behaviour::{BirdBehaviour, BirdState, BirdStateConfig},
+    boss::{Boss, BossConfig},
+    flocking::Flocking,
+    forage::Foraging,
+    movement::{BirdFlight, FlightPattern},
+    perch::Perching,
+    pest::Disposition,
+    temper::BirdTemper,
+};
 use crate::{
-    physics::{Collider, Velocity},
-    util::{AnimationIndices, AnimationTimer, EntityAssetReadyEvent, TargetTransform},
+    level::BirdTweaks,
+    physics::{Collider, SelfDespawning, Velocity},
+    projectile::Food,
+    status::{StatusEffects, StatusSpec},
+    util::{AnimationClip, EntityAssetReadyEvent, SpriteAnimation, SpriteAtlas, TargetTransform},
+    validate::{Validate, ValidationContext},
 };
 
 const DEFAULT_DROPPING_PROBABILITY: f32 = 0.0005;
+/// Clip played when no other is given or the one asked for does not exist.
+const FLY_CLIP: &str = "fly";
+/// Clip played while a bird walks to food on the ground, see [super::forage].
+pub const WALK_CLIP: &str = "walk";
+/// Clip played while a bird rests on a perch, see [super::perch].
+pub const PERCH_CLIP: &str = "perch";
 
 /// Loads asset file and spawns remaining [Bird] components
 /// on entities with a [BirdAssetHandle].
+///
+/// Birds that are already flying when their asset is reloaded keep their hunger, state and heading,
+/// only picking up the changes to the asset, see [reload_bird].
 pub(super) fn load_bird_assets_sys(
     mut cmd: Commands,
     mut asset_events: EventReader<EntityAssetReadyEvent<BirdAsset>>,
     asset_server: Res<AssetServer>,
     assets: Res<Assets<BirdAsset>>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tweaks: Query<&BirdTweaks>,
+    mut live_birds: Query<LiveBird>,
+    mut hunger_bars: Query<&mut Transform, With<BirdHungerBar>>,
+    time: Res<Time>,
 ) {
     for EntityAssetReadyEvent((entities, asset_id)) in asset_events.read() {
         let asset = assets.get(asset_id).expect("asset does not exist");
         for entity in entities {
+            // birds from waves or bosses may not have any tweaks
+            let tweaks = tweaks.get(*entity).cloned().unwrap_or_default();
+
+            if let Ok(live) = live_birds.get_mut(*entity) {
+                let sprite = bird_sprite(asset, &tweaks, &asset_server, &mut texture_atlas_layouts);
+                reload_bird(live, asset, &tweaks, sprite, &mut hunger_bars);
+                if asset.boss.is_some() {
+                    cmd.entity(*entity).insert_if_new(Boss::default());
+                } else {
+                    cmd.entity(*entity).remove::<Boss>();
+                }
+                continue;
+            }
+
+            let velocity = asset.velocity * tweaks.velocity_multiplier;
+            let hunger = asset.hunger.saturating_add(tweaks.hunger_offset).max(1);
+
             let mut target_tf =
                 TargetTransform::new(Transform::IDENTITY, EaseFunction::ExponentialOut);
-            target_tf.duration_factor = asset.velocity * 0.015;
+            target_tf.duration_factor = velocity * 0.015;
             target_tf.lerp_transform = false; // conflicts with movement if enabled
             target_tf.finish();
 
-            // FIXME: if atlas_dimensions are not given, and it gets a sprite sheet with multiple sprites
-            // then it just kinda,, displays all of them squished into one sprite
-            // instead of the intended behaviour which is to just take the first sprite
-            // of the sheet and display that static image
-
-            // columns x rows in the sprite sheet
-            let dimensions = asset.atlas_dimensions.unwrap_or(UVec2 { x: 1, y: 1 });
-            // layout of the sprite sheet
-            let texture_atlas_layout = texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
-                UVec2 { x: 64, y: 32 }, // width x height of each sprite in sheet
-                dimensions[0],          // columns
-                dimensions[1],          // rows
-                None,                   // padding
-                None,                   // offset
-            ));
-
-            let last = usize::try_from((dimensions[0] * dimensions[1]) - 1).unwrap();
-            let animation_indices = AnimationIndices { first: 0, last };
-
-            let mut sprite = Sprite::from_atlas_image(
-                asset_server.load(asset.sprite.clone()),
-                TextureAtlas { layout: texture_atlas_layout, index: animation_indices.first },
-            );
-            sprite.custom_size = Some(asset.size);
-            sprite.flip_y = true; // birds render upside down if disabled
-
-            cmd.entity(*entity)
+            let arriving = asset.state_config(BirdState::Arriving);
+            let animation = SpriteAnimation::new(asset.clip(arriving.animation.as_deref()));
+            let speed = velocity * arriving.speed_multiplier.unwrap_or(1.);
+
+            let mut sprite = bird_sprite(asset, &tweaks, &asset_server, &mut texture_atlas_layouts);
+            if let Some(atlas) = &mut sprite.texture_atlas {
+                atlas.index = animation.index();
+            }
+
+            let mut bird_cmds = cmd.entity(*entity);
+            if asset.boss.is_some() {
+                bird_cmds.insert(Boss::default());
+            }
+            bird_cmds
                 .despawn_related::<Children>()
                 // .remove::<Children>()
                 .insert((
                     Bird {
                         name: asset.name.clone(),
-                        hunger: asset.hunger,
-                        initial_hunger: asset.hunger,
-                        on_feed_points: asset.on_feed_points,
+                        velocity,
+                        hunger,
+                        initial_hunger: hunger,
+                        hunger_elapsed: 0.,
+                        anger: 0.,
                         drop_probability: asset
                             .drop_probability
                             .unwrap_or(DEFAULT_DROPPING_PROBABILITY),
+                        entered_screen: false,
                     },
-                    Velocity(asset.velocity),
+                    BirdBehaviour::new(time.elapsed_secs()),
+                    Velocity(speed),
+                    BirdFlight::new(arriving.movement.unwrap_or_default(), speed),
                     Collider::Rectangle(Rectangle::from_size(asset.size)),
+                    // birds report how they left, see [super::departure::bird_departure_sys]
+                    SelfDespawning,
                     sprite,
-                    animation_indices,
-                    AnimationTimer(Timer::from_seconds(0.25, TimerMode::Repeating)),
+                    animation,
                     target_tf,
+                    StatusEffects::default(),
                 ))
                 .with_child((
                     BirdHungerBar,
@@ -83,18 +122,213 @@ pub(super) fn load_bird_assets_sys(
     }
 }
 
+/// Components of a bird that has already been loaded, see [reload_bird].
+type LiveBird<'a> = (
+    &'a mut Bird,
+    &'a BirdBehaviour,
+    &'a mut BirdFlight,
+    &'a mut Velocity,
+    &'a mut Collider,
+    &'a mut Sprite,
+    &'a mut SpriteAnimation,
+    &'a mut TargetTransform,
+    Option<&'a Children>,
+);
+
+/// Sprite for `asset`, starting on the first frame of its sheet.
+fn bird_sprite(
+    asset: &BirdAsset,
+    tweaks: &BirdTweaks,
+    asset_server: &AssetServer,
+    texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
+) -> Sprite {
+    // sprites without an atlas are shown whole as a static image
+    let image = asset_server.load(asset.sprite.clone());
+    let mut sprite = match &asset.atlas {
+        Some(atlas) => Sprite::from_atlas_image(
+            image,
+            TextureAtlas { layout: texture_atlas_layouts.add(atlas.layout()), index: 0 },
+        ),
+        None => Sprite::from_image(image),
+    };
+    sprite.custom_size = Some(asset.size);
+    sprite.flip_y = true; // birds render upside down if disabled
+    if let Some(tint) = tweaks.tint {
+        sprite.color = tint.into();
+    }
+    sprite
+}
+
+/// Applies a changed asset to a bird that is already flying.
+///
+/// Hunger, anger and state carry over, with hunger kept within the new starting hunger.
+/// Movement is only restarted if the pattern for the current state has changed,
+/// otherwise the bird keeps its heading and only its speed changes.
+fn reload_bird(
+    live: QueryItem<LiveBird>,
+    asset: &BirdAsset,
+    tweaks: &BirdTweaks,
+    new_sprite: Sprite,
+    hunger_bars: &mut Query<&mut Transform, With<BirdHungerBar>>,
+) {
+    debug!("Reloading bird {}", asset.name);
+    let (
+        mut bird,
+        behaviour,
+        mut flight,
+        mut velocity,
+        mut collider,
+        mut sprite,
+        mut animation,
+        mut target_tf,
+        children,
+    ) = live;
+    let velocity_now = asset.velocity * tweaks.velocity_multiplier;
+    let initial_hunger = asset.hunger.saturating_add(tweaks.hunger_offset).max(1);
+    bird.name = asset.name.clone();
+    bird.velocity = velocity_now;
+    bird.initial_hunger = initial_hunger;
+    bird.hunger = bird.hunger.min(initial_hunger);
+    bird.drop_probability = asset.drop_probability.unwrap_or(DEFAULT_DROPPING_PROBABILITY);
+
+    let config = asset.state_config(behaviour.state());
+    let speed = velocity_now * config.speed_multiplier.unwrap_or(1.);
+    flight.reload(config.movement.unwrap_or_default(), speed);
+    velocity.0 = speed;
+    target_tf.duration_factor = velocity_now * 0.015;
+
+    *collider = Collider::Rectangle(Rectangle::from_size(asset.size));
+    *sprite = new_sprite;
+    // restart the clip in case its frames have moved
+    *animation = SpriteAnimation::new(asset.clip(config.animation.as_deref()));
+
+    for child in children.into_iter().flatten() {
+        if let Ok(mut bar_tf) = hunger_bars.get_mut(*child) {
+            bar_tf.translation.x = asset.size.x * 0.6;
+        }
+    }
+}
+
 #[derive(Asset, TypePath, Debug, Deserialize, Default)]
 pub struct BirdAsset {
     pub name: String,
+    /// Flavour text shown in the Birdpedia.
+    #[serde(default)]
+    pub description: String,
     pub hunger: i8,
     pub size: Vec2,
     pub sprite: PathBuf,
     pub velocity: f32,
     pub on_feed_points: u32,
-    /// Columns and rows in sprite sheet
-    atlas_dimensions: Option<UVec2>,
+    /// Whether the bird is worth feeding at all.
+    #[serde(default)]
+    pub disposition: Disposition,
+    /// Points lost if the bird leaves the screen while still hungry.
+    #[serde(default)]
+    pub escape_penalty: u32,
+    /// Layout of the frames in `sprite`, if it is a sprite sheet.
+    #[serde(default)]
+    pub atlas: Option<SpriteAtlas>,
+    /// Named ranges of frames from the sprite sheet, such as `fly`, `flap`, `eat` and `flee`.
+    #[serde(default)]
+    pub clips: HashMap<String, AnimationClip>,
     pub drop_probability: Option<f32>,
     pub droppings: Option<Vec<BirdAssetDroppingOption>>,
+    #[serde(default)]
+    pub movement: FlightPattern,
+    /// Overrides for how the bird behaves in each [BirdState].
+    #[serde(default)]
+    pub states: HashMap<BirdState, BirdStateConfig>,
+    /// Steering used to keep clear of solid scenery.
+    #[serde(default)]
+    pub avoidance: Avoidance,
+    /// Steering used to keep together when released as a flock.
+    #[serde(default)]
+    pub flocking: Option<Flocking>,
+    /// How the bird goes after food on the ground. Birds without it only eat food thrown at them.
+    #[serde(default)]
+    pub foraging: Option<Foraging>,
+    /// How often the bird rests on scenery. Birds without it never perch.
+    #[serde(default)]
+    pub perching: Option<Perching>,
+    /// How the bird feels about each [Food]. Foods not listed are liked.
+    #[serde(default)]
+    pub foods: HashMap<Food, BirdAssetFood>,
+    /// How the bird reacts to being ignored. Birds without a temper wait patiently.
+    #[serde(default)]
+    pub temper: Option<BirdTemper>,
+    /// Phases that make this bird a boss.
+    #[serde(default)]
+    pub boss: Option<BossConfig>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum FoodPreference {
+    #[default]
+    Likes,
+    /// Eaten, but worth half the points by default.
+    Dislikes,
+    /// Not eaten at all.
+    Refuses,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct BirdAssetFood {
+    pub preference: FoodPreference,
+    /// Units of hunger satisfied by one of this food. Defaults to 1.
+    pub hunger: Option<i8>,
+    /// Points for feeding one of this food. Defaults to [BirdAsset::on_feed_points].
+    pub points: Option<u32>,
+    /// Status effect the food has on the bird, such as slowing it down.
+    pub status: Option<StatusSpec>,
+}
+
+/// Effect of feeding a bird one unit of a [Food].
+#[derive(Debug, Clone, Copy)]
+pub struct Feeding {
+    pub hunger: i8,
+    pub points: u32,
+    pub status: Option<StatusSpec>,
+}
+
+impl BirdAsset {
+    /// What feeding the bird `food` does, or `None` if the bird refuses it.
+    pub fn feeding(&self, food: Food) -> Option<Feeding> {
+        let config = self.foods.get(&food);
+        let preference = config.map_or(FoodPreference::Likes, |c| c.preference);
+        let default_points = match preference {
+            FoodPreference::Likes => self.on_feed_points,
+            FoodPreference::Dislikes => self.on_feed_points / 2,
+            FoodPreference::Refuses => return None,
+        };
+        Some(Feeding {
+            hunger: config.and_then(|c| c.hunger).unwrap_or(1),
+            points: config.and_then(|c| c.points).unwrap_or(default_points),
+            status: config.and_then(|c| c.status),
+        })
+    }
+
+    /// Number of frames in the sprite sheet.
+    pub fn frame_count(&self) -> usize {
+        self.atlas.as_ref().map_or(1, SpriteAtlas::frame_count)
+    }
+
+    /// Clip called `name`, falling back to the `fly` clip and then to looping the whole sheet.
+    pub fn clip(&self, name: Option<&str>) -> AnimationClip {
+        name.and_then(|name| self.clips.get(name))
+            .or_else(|| self.clips.get(FLY_CLIP))
+            .cloned()
+            .unwrap_or_else(|| AnimationClip::all_frames(self.frame_count()))
+    }
+
+    fn check_clip(&self, name: Option<&str>, ctx: &mut ValidationContext) {
+        if let Some(name) = name
+            && !self.clips.contains_key(name)
+        {
+            ctx.error(name, format!("unknown animation clip {}", name));
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -102,3 +336,173 @@ pub struct BirdAssetDroppingOption {
     pub probability: f32,
     pub asset: PathBuf,
 }
+
+impl Validate for BirdAsset {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        // Hunger bar progress is calculated as a fraction of initial hunger.
+        if self.hunger <= 0 {
+            ctx.error("hunger", format!("hunger must be greater than 0, found {}", self.hunger));
+        }
+        if self.size.x <= 0. || self.size.y <= 0. {
+            ctx.error("size", "size must be positive");
+        }
+        if let Some(atlas) = &self.atlas {
+            atlas.validate(ctx);
+        }
+        for (name, clip) in &self.clips {
+            clip.validate(name, self.frame_count(), ctx);
+        }
+        if let Some(drop_probability) = self.drop_probability {
+            ctx.check_probability(drop_probability, "drop_probability");
+        }
+
+        self.movement.validate(ctx);
+        for (food, config) in &self.foods {
+            let near = format!("{:?}", food);
+            if config.preference == FoodPreference::Refuses
+                && (config.hunger.is_some() || config.points.is_some())
+            {
+                ctx.error(&near, format!("{:?} is refused so its hunger and points are never used", food));
+            }
+            if config.hunger.is_some_and(|hunger| hunger < 0) {
+                ctx.error(&near, format!("hunger for {:?} must not be negative", food));
+            }
+            if let Some(status) = &config.status {
+                status.validate(ctx);
+            }
+        }
+        if let Some(temper) = &self.temper {
+            temper.validate(ctx);
+        }
+        if let Some(boss) = &self.boss {
+            boss.validate(ctx);
+            for phase in &boss.phases {
+                self.check_clip(phase.animation.as_deref(), ctx);
+            }
+        }
+        if let Some(flocking) = &self.flocking {
+            flocking.validate(ctx);
+        }
+        self.avoidance.validate(ctx);
+        if let Some(foraging) = &self.foraging {
+            foraging.validate(ctx);
+        }
+        if let Some(perching) = &self.perching {
+            perching.validate(ctx);
+        }
+        for config in self.states.values() {
+            config.validate(ctx);
+            self.check_clip(config.animation.as_deref(), ctx);
+        }
+
+        ctx.check_asset_exists(&self.sprite);
+        for dropping in self.droppings.iter().flatten() {
+            ctx.check_weight(dropping.probability, &dropping.asset.to_string_lossy());
+            ctx.check_ron_asset(&dropping.asset);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+    use crate::status::StatusKind;
+
+    fn goose() -> BirdAsset {
+        ron::de::from_str(include_str!("../../assets/birds/canada_goose.ron")).unwrap()
+    }
+
+    #[test]
+    fn preferred_food_uses_its_overrides() {
+        let feeding = goose().feeding(Food::Bread).unwrap();
+        assert_eq!(feeding.hunger, 3);
+        assert_eq!(feeding.points, 40);
+        assert_eq!(feeding.status.map(|status| status.kind), Some(StatusKind::Slowed));
+    }
+
+    #[test]
+    fn disliked_food_is_worth_half_points() {
+        let feeding = goose().feeding(Food::Seeds).unwrap();
+        assert_eq!(feeding.hunger, 1);
+        assert_eq!(feeding.points, 12);
+        assert_eq!(feeding.status.map(|status| status.kind), Some(StatusKind::Enraged));
+    }
+
+    #[test]
+    fn refused_food_is_not_eaten() {
+        assert!(goose().feeding(Food::Berries).is_none());
+    }
+
+    #[test]
+    fn unlisted_food_is_liked() {
+        let feeding = goose().feeding(Food::Worms).unwrap();
+        assert_eq!(feeding.hunger, 1);
+        assert_eq!(feeding.points, 25);
+        assert!(feeding.status.is_none());
+    }
+
+    #[test]
+    fn bird_without_preferences_eats_everything() {
+        let mut asset = goose();
+        asset.foods.clear();
+        for food in Food::ALL {
+            let feeding = asset.feeding(food).unwrap();
+            assert_eq!((feeding.hunger, feeding.points), (1, 25), "{:?}", food);
+            assert!(feeding.status.is_none());
+        }
+    }
+
+    #[test]
+    fn reloading_clamps_hunger_and_keeps_state() {
+        let mut world = World::new();
+        let old = goose();
+        let bar = world.spawn((BirdHungerBar, Transform::default())).id();
+        let mut behaviour = BirdBehaviour::new(0.);
+        behaviour.transition(Entity::PLACEHOLDER, BirdState::Satisfied, 0.);
+        let bird = world
+            .spawn((
+                Bird {
+                    name: old.name.clone(),
+                    velocity: old.velocity,
+                    hunger: 6,
+                    initial_hunger: old.hunger,
+                    hunger_elapsed: 0.,
+                    anger: 0.5,
+                    drop_probability: 0.,
+                    entered_screen: true,
+                },
+                behaviour,
+                BirdFlight::new(FlightPattern::Straight, old.velocity * 2.),
+                Velocity(old.velocity * 2.),
+                Collider::Rectangle(Rectangle::from_size(old.size)),
+                Sprite::default(),
+                SpriteAnimation::new(old.clip(None)),
+                TargetTransform::new(Transform::default(), EaseFunction::Linear),
+            ))
+            .add_child(bar)
+            .id();
+
+        let mut new = goose();
+        new.hunger = 4;
+        new.velocity = 120.;
+        new.size = Vec2::new(100., 50.);
+        let mut state =
+            SystemState::<(Query<LiveBird>, Query<&mut Transform, With<BirdHungerBar>>)>::new(&mut world);
+        let (mut live_birds, mut hunger_bars) = state.get_mut(&mut world);
+        let live = live_birds.get_mut(bird).unwrap();
+        reload_bird(live, &new, &BirdTweaks::default(), Sprite::default(), &mut hunger_bars);
+        state.apply(&mut world);
+
+        let reloaded = world.get::<Bird>(bird).unwrap();
+        assert_eq!((reloaded.hunger, reloaded.initial_hunger), (4, 4));
+        assert_eq!(reloaded.velocity, 120.);
+        assert_eq!(reloaded.anger, 0.5);
+        assert_eq!(world.get::<BirdBehaviour>(bird).unwrap().state(), BirdState::Satisfied);
+        let speed = 120. * new.state_config(BirdState::Satisfied).speed_multiplier.unwrap();
+        assert_eq!(world.get::<BirdFlight>(bird).unwrap().speed, speed);
+        assert_eq!(world.get::<Velocity>(bird).unwrap().0, speed);
+        assert_eq!(world.get::<Transform>(bar).unwrap().translation.x, new.size.x * 0.6);
+    }
+}
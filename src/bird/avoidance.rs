@@ -0,0 +1,102 @@
+use bevy::{
+    math::bounding::{Aabb2d, BoundingVolume, RayCast2d},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use super::{
+    asset::BirdAsset,
+    behaviour::{BirdBehaviour, BirdState},
+    movement::BirdFlight,
+};
+use crate::{
+    level::Scenery,
+    physics::{Collider, ColliderOffset, ColliderStatic},
+    util::AssetHandle,
+    validate::{Validate, ValidationContext},
+};
+
+/// How a bird steers around solid scenery in its way.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct Avoidance {
+    /// Distance ahead of the bird checked for scenery, `0` disables avoidance.
+    pub look_ahead: f32,
+    /// Fastest the bird turns away from scenery, in degrees per second.
+    pub turn_rate: f32,
+}
+
+impl Default for Avoidance {
+    fn default() -> Self {
+        Self { look_ahead: 200., turn_rate: 150. }
+    }
+}
+
+impl Validate for Avoidance {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        if self.look_ahead < 0. {
+            ctx.error("look_ahead", "look_ahead must not be negative");
+        }
+        if self.turn_rate < 0. {
+            ctx.error("turn_rate", "turn_rate must not be negative");
+        }
+    }
+}
+
+/// Turns birds away from [ColliderStatic] scenery that lies ahead of them, toward open sky.
+///
+/// Scenery is widened by half the bird's size so the bird clears it rather than clipping its edge.
+/// Birds heading somewhere on purpose, such as food on the ground or a perch, are left alone.
+pub(super) fn bird_avoid_sys(
+    mut birds: Query<(&mut BirdFlight, &Transform, &BirdBehaviour, &AssetHandle<BirdAsset>)>,
+    obstacles: Query<
+        (&Collider, &Transform, Option<&ColliderOffset>),
+        (With<ColliderStatic>, With<Scenery>, Without<BirdFlight>),
+    >,
+    assets: Res<Assets<BirdAsset>>,
+    time: Res<Time>,
+) {
+    let obstacles: Vec<Aabb2d> =
+        obstacles.iter().map(|(collider, tf, offset)| collider.aabb(tf, offset)).collect();
+    if obstacles.is_empty() {
+        return;
+    }
+
+    for (mut flight, tf, behaviour, handle) in birds.iter_mut() {
+        if matches!(
+            behaviour.state(),
+            BirdState::Foraging | BirdState::Perching | BirdState::Perched
+        ) {
+            continue;
+        }
+        let Some(asset) = assets.get(&handle.0) else {
+            continue;
+        };
+        let avoidance = &asset.avoidance;
+        if avoidance.look_ahead <= 0. {
+            continue;
+        }
+        let position = tf.translation.xy();
+        let Ok(direction) = Dir2::new((tf.rotation * Vec3::Y).xy()) else {
+            continue;
+        };
+
+        let ray = RayCast2d::new(position, direction, avoidance.look_ahead);
+        let margin = Vec2::splat(asset.size.min_element() * 0.5);
+        let closest = obstacles
+            .iter()
+            .map(|aabb| aabb.grow(margin))
+            .filter_map(|aabb| ray.aabb_intersection_at(&aabb).map(|distance| (aabb, distance)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((aabb, _)) = closest else {
+            continue;
+        };
+
+        // turn away from the side the obstacle's middle is on
+        let to_obstacle = aabb.center() - position;
+        let side = if direction.perp_dot(to_obstacle) > 0. { -1. } else { 1. };
+        let turn = side * avoidance.turn_rate.to_radians() * time.delta_secs();
+
+        flight.steer(turn);
+    }
+}
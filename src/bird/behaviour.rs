@@ -0,0 +1,303 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{
+    Bird,
+    asset::{BirdAsset, PERCH_CLIP},
+    departure::exit_direction,
+    movement::{BirdFlight, FlightPattern},
+};
+use crate::{
+    physics::Velocity,
+    util::{AssetHandle, SpriteAnimation, TargetTransform},
+    validate::{Validate, ValidationContext},
+};
+
+/// What a bird is currently doing.
+///
+/// Each state has its own movement, animation and timeout, see [BirdStateConfig].
+/// Birds move between states when they time out or when something happens to them, such as being fed.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BirdState {
+    /// Entering the play area.
+    #[default]
+    Arriving,
+    /// Flying around looking for food.
+    Hungry,
+    /// Waiting around for food once it has been hungry for a while.
+    Circling,
+    /// Fed up with being ignored, see [super::temper::BirdTemper].
+    Angry,
+    /// Going after food on the ground, landing and walking the last stretch, see [super::forage].
+    Foraging,
+    /// Flying to a free [super::perch::Perch] to rest on.
+    Perching,
+    /// Resting on a [super::perch::Perch] until it takes off again. Can still be fed.
+    Perched,
+    /// Has just been fed and will not accept more food until it has finished.
+    Eating,
+    /// No longer hungry and flying off happily.
+    Satisfied,
+    /// Scared off.
+    Fleeing,
+    /// Heading out of the play area for good.
+    Leaving,
+}
+
+impl BirdState {
+    /// Whether the bird will accept food.
+    pub fn is_hungry(self) -> bool {
+        matches!(
+            self,
+            BirdState::Arriving
+                | BirdState::Hungry
+                | BirdState::Circling
+                | BirdState::Angry
+                | BirdState::Foraging
+                | BirdState::Perching
+                | BirdState::Perched
+        )
+    }
+
+    /// Whether the bird still cares about food, so should show its hunger.
+    pub fn shows_hunger(self) -> bool {
+        !matches!(self, BirdState::Satisfied | BirdState::Leaving)
+    }
+
+    /// State to move to once the timeout for this state has passed.
+    fn after_timeout(self, hunger: i8) -> BirdState {
+        match self {
+            BirdState::Arriving => BirdState::Hungry,
+            BirdState::Hungry => BirdState::Circling,
+            BirdState::Circling | BirdState::Angry => BirdState::Leaving,
+            BirdState::Foraging | BirdState::Perching | BirdState::Perched => BirdState::Hungry,
+            BirdState::Eating | BirdState::Fleeing if hunger > 0 => BirdState::Hungry,
+            BirdState::Eating => BirdState::Satisfied,
+            BirdState::Fleeing | BirdState::Satisfied | BirdState::Leaving => BirdState::Leaving,
+        }
+    }
+
+    fn default_config(self) -> BirdStateConfig {
+        match self {
+            BirdState::Arriving => BirdStateConfig { timeout: Some(0.), ..default() },
+            BirdState::Hungry => default(),
+            BirdState::Circling => BirdStateConfig {
+                movement: Some(FlightPattern::Circle { delay: 0., radius: 150., laps: f32::INFINITY }),
+                ..default()
+            },
+            BirdState::Angry => BirdStateConfig {
+                movement: Some(FlightPattern::Dive { range: 600., speed_multiplier: 2., turn_rate: 120. }),
+                ..default()
+            },
+            BirdState::Foraging => BirdStateConfig {
+                movement: Some(FlightPattern::Straight),
+                timeout: Some(8.),
+                ..default()
+            },
+            BirdState::Perching => BirdStateConfig {
+                movement: Some(FlightPattern::Straight),
+                timeout: Some(8.),
+                ..default()
+            },
+            BirdState::Perched => BirdStateConfig {
+                movement: Some(FlightPattern::Straight),
+                speed_multiplier: Some(0.),
+                animation: Some(PERCH_CLIP.into()),
+                timeout: Some(4.),
+            },
+            BirdState::Eating => BirdStateConfig {
+                movement: Some(FlightPattern::Hover { delay: 0., duration: f32::INFINITY }),
+                animation: Some("eat".into()),
+                timeout: Some(0.3),
+                ..default()
+            },
+            BirdState::Satisfied => BirdStateConfig {
+                movement: Some(FlightPattern::Straight),
+                speed_multiplier: Some(2.),
+                animation: Some("flap".into()),
+                timeout: Some(1.),
+                ..default()
+            },
+            BirdState::Fleeing => BirdStateConfig {
+                movement: Some(FlightPattern::Straight),
+                speed_multiplier: Some(3.),
+                animation: Some("flee".into()),
+                timeout: Some(1.),
+                ..default()
+            },
+            BirdState::Leaving => BirdStateConfig {
+                movement: Some(FlightPattern::Straight),
+                ..default()
+            },
+        }
+    }
+
+    /// Whether the bird should turn away on entering the state.
+    fn turns_away(self) -> bool {
+        matches!(self, BirdState::Fleeing)
+    }
+
+    /// Whether the bird should head for the closest edge of the screen on entering the state.
+    fn heads_off_screen(self) -> bool {
+        matches!(self, BirdState::Satisfied | BirdState::Leaving)
+    }
+}
+
+/// How a bird behaves in a [BirdState].
+///
+/// Anything not given falls back to a default for the state,
+/// or to the bird's own movement and animation.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct BirdStateConfig {
+    pub movement: Option<FlightPattern>,
+    /// Multiplier applied to the bird's velocity.
+    pub speed_multiplier: Option<f32>,
+    /// Name of the [crate::util::AnimationClip] to play.
+    pub animation: Option<String>,
+    /// Seconds until the bird moves on to its next state. Never times out if not given.
+    pub timeout: Option<f32>,
+}
+
+impl Validate for BirdStateConfig {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        if let Some(movement) = &self.movement {
+            movement.validate(ctx);
+        }
+        if self.speed_multiplier.is_some_and(|m| m < 0.) {
+            ctx.error("speed_multiplier", "speed_multiplier must not be negative");
+        }
+        if self.timeout.is_some_and(|t| t < 0.) {
+            ctx.error("timeout", "timeout must not be negative");
+        }
+    }
+}
+
+impl BirdAsset {
+    /// Configuration for `state`, with defaults filled in.
+    pub fn state_config(&self, state: BirdState) -> BirdStateConfig {
+        let defaults = state.default_config();
+        let config = self.states.get(&state).cloned().unwrap_or_default();
+        BirdStateConfig {
+            movement: config.movement.or(defaults.movement).or(Some(self.movement.clone())),
+            speed_multiplier: config.speed_multiplier.or(defaults.speed_multiplier).or(Some(1.)),
+            animation: config.animation.or(defaults.animation),
+            timeout: config.timeout.or(defaults.timeout),
+        }
+    }
+}
+
+/// Current [BirdState] of a bird.
+#[derive(Debug, Component)]
+pub struct BirdBehaviour {
+    state: BirdState,
+    /// Elapsed time when the current state was entered.
+    entered_at: f32,
+}
+
+impl BirdBehaviour {
+    pub fn new(now: f32) -> Self {
+        Self { state: BirdState::Arriving, entered_at: now }
+    }
+
+    pub fn state(&self) -> BirdState {
+        self.state
+    }
+
+    /// Moves to state `to`, returning the event to send if the state has changed.
+    pub fn transition(&mut self, bird: Entity, to: BirdState, now: f32) -> Option<BirdStateEvent> {
+        if self.state == to {
+            return None;
+        }
+        let from = self.state;
+        self.state = to;
+        self.entered_at = now;
+        Some(BirdStateEvent { bird, from, to })
+    }
+}
+
+/// Sent whenever a bird changes [BirdState].
+#[derive(Debug, Event)]
+pub struct BirdStateEvent {
+    pub bird: Entity,
+    pub from: BirdState,
+    pub to: BirdState,
+}
+
+/// Moves birds on to their next state once they have been in their current state for too long.
+pub(super) fn bird_state_timeout_sys(
+    mut birds: Query<(Entity, &mut BirdBehaviour, &Bird, &AssetHandle<BirdAsset>)>,
+    mut state_evw: EventWriter<BirdStateEvent>,
+    assets: Res<Assets<BirdAsset>>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs();
+    for (entity, mut behaviour, bird, handle) in birds.iter_mut() {
+        let Some(asset) = assets.get(&handle.0) else {
+            continue;
+        };
+
+        let state = behaviour.state;
+        if let Some(timeout) = asset.state_config(state).timeout
+            && now - behaviour.entered_at >= timeout
+            && let Some(evt) = behaviour.transition(entity, state.after_timeout(bird.hunger), now)
+        {
+            state_evw.write(evt);
+        }
+    }
+}
+
+/// Applies the movement and animation of the state a bird has just entered.
+pub(super) fn enter_bird_state_sys(
+    mut state_evr: EventReader<BirdStateEvent>,
+    mut birds: Query<(
+        &Bird,
+        &mut BirdFlight,
+        &mut Velocity,
+        &Transform,
+        &mut TargetTransform,
+        &mut SpriteAnimation,
+        &AssetHandle<BirdAsset>,
+    )>,
+    assets: Res<Assets<BirdAsset>>,
+    windows: Query<&Window>,
+) {
+    let mut rng = rand::rng();
+    let half_size = windows.single().map_or(Vec2::ONE, |window| window.size() * 0.5);
+    for evt in state_evr.read() {
+        debug!("Bird {} went from {:?} to {:?}", evt.bird, evt.from, evt.to);
+        let Ok((bird, mut flight, mut velocity, tf, mut target_tf, mut animation, handle)) =
+            birds.get_mut(evt.bird)
+        else {
+            continue;
+        };
+        let Some(asset) = assets.get(&handle.0) else {
+            continue;
+        };
+
+        let config = asset.state_config(evt.to);
+        flight.set_pattern(
+            config.movement.unwrap_or_default(),
+            bird.velocity * config.speed_multiplier.unwrap_or(1.),
+        );
+        velocity.0 = flight.speed;
+
+        if evt.to.heads_off_screen() {
+            // turn in world space to keep the bird's flip
+            let exit = exit_direction(tf.translation.xy(), half_size);
+            let mut new_target_tf = *tf;
+            new_target_tf.rotation = Quat::from_rotation_z(flight.forward(tf).angle_to(exit)) * tf.rotation;
+            target_tf.update(new_target_tf);
+        } else if evt.to.turns_away() {
+            let mut new_target_tf = *tf;
+            new_target_tf.rotate_local_z(if rng.random_bool(0.5) { -2. } else { 2. });
+            target_tf.update(new_target_tf);
+        } else {
+            // stop any turn in progress fighting with the new movement
+            target_tf.finish();
+        }
+
+        animation.play(asset.clip(config.animation.as_deref()));
+    }
+}
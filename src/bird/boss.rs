@@ -0,0 +1,347 @@
+use std::{f32::consts::PI, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    Bird,
+    asset::BirdAsset,
+    behaviour::{BirdBehaviour, BirdStateEvent},
+    dropping::spawn_random_dropping,
+    movement::{BirdFlight, FlightPattern},
+};
+use crate::{
+    level::LevelRootEntity,
+    physics::Velocity,
+    util::{AssetHandle, SpriteAnimation},
+    validate::{Validate, ValidationContext},
+};
+
+/// Speed droppings in a barrage are thrown at, as bosses may be hovering when they drop them.
+const BARRAGE_SPEED: f32 = 150.;
+const BOSS_BAR_WIDTH: f32 = 600.;
+
+/// Makes a bird a boss, which changes how it behaves as it gets fed.
+#[derive(Debug, Deserialize, Clone)]
+pub struct BossConfig {
+    /// Phases in order, each starting once hunger drops low enough.
+    pub phases: Vec<BossPhase>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BossPhase {
+    /// Phase starts once the remaining hunger is at or below this fraction of the starting hunger,
+    /// so `1` starts from the beginning.
+    pub hunger_below: f32,
+    /// Movement while hungry. Falls back to the bird's own movement.
+    #[serde(default)]
+    pub movement: Option<FlightPattern>,
+    #[serde(default)]
+    pub speed_multiplier: Option<f32>,
+    /// Name of the [crate::util::AnimationClip] to play while hungry.
+    #[serde(default)]
+    pub animation: Option<String>,
+    #[serde(default)]
+    pub barrage: Option<DroppingBarrage>,
+    /// Birds released when the phase starts.
+    #[serde(default)]
+    pub minions: Vec<BossMinion>,
+}
+
+/// Droppings thrown in a fan at regular intervals.
+#[derive(Debug, Deserialize, Clone)]
+pub struct DroppingBarrage {
+    /// Seconds between each fan of droppings.
+    pub interval: f32,
+    pub count: u32,
+    /// Angle between the outermost droppings, in degrees.
+    pub spread: f32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BossMinion {
+    pub asset: PathBuf,
+    pub count: u32,
+}
+
+impl BossConfig {
+    /// Index of the phase for `hunger` out of `initial_hunger`, if any has started.
+    fn phase_at(&self, hunger: i8, initial_hunger: i8) -> Option<usize> {
+        let fraction = hunger as f32 / initial_hunger as f32;
+        self.phases.iter().rposition(|phase| fraction <= phase.hunger_below)
+    }
+}
+
+impl Validate for BossConfig {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        if self.phases.is_empty() {
+            ctx.error("phases", "boss must have at least one phase");
+        }
+        for (i, phase) in self.phases.iter().enumerate() {
+            if !(0. ..=1.).contains(&phase.hunger_below) {
+                ctx.error("hunger_below", "hunger_below must be between 0 and 1");
+            }
+            if i > 0 && phase.hunger_below >= self.phases[i - 1].hunger_below {
+                ctx.error("hunger_below", "boss phases must be in order of decreasing hunger_below");
+            }
+            if let Some(movement) = &phase.movement {
+                movement.validate(ctx);
+            }
+            if let Some(barrage) = &phase.barrage
+                && barrage.interval <= 0.
+            {
+                ctx.error("interval", "barrage interval must be positive");
+            }
+            for minion in &phase.minions {
+                ctx.check_ron_asset(&minion.asset);
+            }
+        }
+    }
+}
+
+/// Runtime state for a bird with a [BossConfig].
+#[derive(Debug, Component, Default)]
+pub struct Boss {
+    phase: Option<usize>,
+    barrage_elapsed: f32,
+}
+
+/// Moves bosses between phases as they are fed, applying each phase's movement and animation
+/// and releasing its minions.
+///
+/// Phase movement is also reapplied whenever the boss goes back to being hungry,
+/// as states such as eating bring their own movement.
+pub(super) fn boss_phase_sys(
+    mut cmd: Commands,
+    mut state_evr: EventReader<BirdStateEvent>,
+    mut bosses: Query<(
+        Entity,
+        &mut Boss,
+        &Bird,
+        &BirdBehaviour,
+        &Transform,
+        &mut BirdFlight,
+        &mut Velocity,
+        &mut SpriteAnimation,
+        &AssetHandle<BirdAsset>,
+    )>,
+    level: LevelRootEntity,
+    asset_server: Res<AssetServer>,
+    assets: Res<Assets<BirdAsset>>,
+) {
+    let rehungry: Vec<Entity> = state_evr
+        .read()
+        .filter(|evt| evt.to.is_hungry())
+        .map(|evt| evt.bird)
+        .collect();
+
+    for (entity, mut boss, bird, behaviour, tf, mut flight, mut velocity, mut animation, handle) in
+        bosses.iter_mut()
+    {
+        let Some(asset) = assets.get(&handle.0) else {
+            continue;
+        };
+        let Some(config) = &asset.boss else {
+            continue;
+        };
+
+        let phase_index = config.phase_at(bird.hunger, bird.initial_hunger);
+        let new_phase = phase_index != boss.phase;
+        if new_phase {
+            debug!("Boss {} entered phase {:?}", bird.name, phase_index);
+            boss.phase = phase_index;
+            boss.barrage_elapsed = 0.;
+        }
+        let Some(phase) = phase_index.map(|i| &config.phases[i]) else {
+            continue;
+        };
+
+        if new_phase {
+            cmd.entity(*level).with_children(|builder| {
+                for minion in &phase.minions {
+                    for _ in 0..minion.count {
+                        builder.spawn((AssetHandle::<BirdAsset>(asset_server.load(minion.asset.clone())), *tf));
+                    }
+                }
+            });
+        }
+
+        if behaviour.state().is_hungry() && (new_phase || rehungry.contains(&entity)) {
+            let speed = bird.velocity * phase.speed_multiplier.unwrap_or(1.);
+            flight.set_pattern(phase.movement.clone().unwrap_or(asset.movement.clone()), speed);
+            velocity.0 = speed;
+            if phase.animation.is_some() {
+                animation.play(asset.clip(phase.animation.as_deref()));
+            }
+        }
+    }
+}
+
+/// Throws fans of droppings from hungry bosses in a phase with a [DroppingBarrage].
+pub(super) fn boss_barrage_sys(
+    mut cmd: Commands,
+    mut bosses: Query<(&mut Boss, &BirdBehaviour, &Transform, &AssetHandle<BirdAsset>)>,
+    level: LevelRootEntity,
+    asset_server: Res<AssetServer>,
+    assets: Res<Assets<BirdAsset>>,
+    time: Res<Time>,
+) {
+    let mut rng = rand::rng();
+    for (mut boss, behaviour, tf, handle) in bosses.iter_mut() {
+        if !behaviour.state().is_hungry() {
+            continue;
+        }
+        let Some(asset) = assets.get(&handle.0) else {
+            continue;
+        };
+        let Some(barrage) = boss
+            .phase
+            .and_then(|i| asset.boss.as_ref()?.phases.get(i)?.barrage.as_ref())
+        else {
+            continue;
+        };
+
+        boss.barrage_elapsed += time.delta_secs();
+        if boss.barrage_elapsed < barrage.interval {
+            continue;
+        }
+        boss.barrage_elapsed -= barrage.interval;
+
+        // fan out around straight down the screen
+        let spread = barrage.spread.to_radians();
+        for i in 0..barrage.count {
+            let s = if barrage.count > 1 { i as f32 / (barrage.count - 1) as f32 - 0.5 } else { 0. };
+            let mut dropping_tf = *tf;
+            dropping_tf.translation.z -= 1.;
+            dropping_tf.rotation = Quat::from_rotation_z(PI + spread * s);
+            spawn_random_dropping(
+                &mut cmd,
+                *level,
+                &asset_server,
+                asset,
+                dropping_tf,
+                BARRAGE_SPEED,
+                &mut rng,
+            );
+        }
+    }
+}
+
+#[derive(Component)]
+pub(super) struct BossHud;
+
+#[derive(Component)]
+pub(super) struct BossHudName;
+
+#[derive(Component)]
+pub(super) struct BossHudFill;
+
+/// Shows how well fed the current boss is at the top of the screen,
+/// for as long as a boss is still hungry.
+pub(super) fn update_boss_hud_sys(
+    mut cmd: Commands,
+    bosses: Query<(&Bird, &BirdBehaviour), With<Boss>>,
+    hud: Query<Entity, With<BossHud>>,
+    mut fill: Query<&mut Node, With<BossHudFill>>,
+    mut name: Query<&mut Text, With<BossHudName>>,
+    asset_server: Res<AssetServer>,
+) {
+    let boss = bosses.iter().find(|(_, behaviour)| behaviour.state().shows_hunger());
+    let Some((bird, _)) = boss else {
+        for entity in hud.iter() {
+            cmd.entity(entity).despawn();
+        }
+        return;
+    };
+
+    let percent_full = (bird.initial_hunger - bird.hunger) as f32 / bird.initial_hunger as f32;
+    if !hud.is_empty() {
+        if let Ok(mut fill_node) = fill.single_mut() {
+            fill_node.width = Val::Percent(100. * percent_full);
+        }
+        if let Ok(mut name) = name.single_mut()
+            && name.0 != bird.name
+        {
+            name.0 = bird.name.clone();
+        }
+        return;
+    }
+
+    cmd.spawn((
+        BossHud,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.),
+            left: Val::Percent(50.),
+            margin: UiRect::left(Val::Px(-BOSS_BAR_WIDTH * 0.5)),
+            width: Val::Px(BOSS_BAR_WIDTH),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        children![
+            (
+                BossHudName,
+                Text::new(bird.name.clone()),
+                TextFont {
+                    font: asset_server.load(PathBuf::from("fonts").join("NewHiScore.ttf")),
+                    font_size: 30.,
+                    ..default()
+                },
+            ),
+            (
+                Node { width: Val::Percent(100.), height: Val::Px(12.), ..default() },
+                BackgroundColor(bevy::color::palettes::css::ORANGE.into()),
+                children![(
+                    BossHudFill,
+                    Node {
+                        width: Val::Percent(100. * percent_full),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    BackgroundColor(bevy::color::palettes::css::GREEN.into()),
+                )],
+            ),
+        ],
+    ));
+}
+
+/// Removes the boss hunger bar when leaving the level.
+pub(super) fn despawn_boss_hud_sys(mut cmd: Commands, hud: Query<Entity, With<BossHud>>) {
+    for entity in hud.iter() {
+        cmd.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(thresholds: &[f32]) -> BossConfig {
+        let phases: Vec<String> = thresholds
+            .iter()
+            .map(|hunger_below| format!("( hunger_below: {} )", hunger_below))
+            .collect();
+        ron::de::from_str(&format!("( phases: [{}] )", phases.join(", "))).unwrap()
+    }
+
+    #[test]
+    fn phase_at_each_threshold() {
+        let config = config(&[1., 0.6, 0.25]);
+        assert_eq!(config.phase_at(20, 20), Some(0));
+        assert_eq!(config.phase_at(13, 20), Some(0));
+        // phases start once hunger is at their fraction
+        assert_eq!(config.phase_at(12, 20), Some(1));
+        assert_eq!(config.phase_at(6, 20), Some(1));
+        assert_eq!(config.phase_at(5, 20), Some(2));
+        assert_eq!(config.phase_at(0, 20), Some(2));
+    }
+
+    #[test]
+    fn no_phase_before_the_first_threshold() {
+        let config = config(&[0.5]);
+        assert_eq!(config.phase_at(20, 20), None);
+        assert_eq!(config.phase_at(11, 20), None);
+        assert_eq!(config.phase_at(10, 20), Some(0));
+    }
+}
@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+
+use super::{
+    Bird,
+    asset::BirdAsset,
+    behaviour::{BirdBehaviour, BirdState},
+};
+use crate::{
+    score::{ScoreEvent, ScoreReason},
+    util::AssetHandle,
+};
+
+/// Distance past the edge of the screen a bird must be before it has left,
+/// on top of its own size.
+const DEPARTURE_MARGIN: f32 = 20.;
+/// Distance past the edge of the screen at which birds that never made it on screen are cleared up.
+const STRAY_MARGIN: f32 = 800.;
+
+/// Sent when a bird first flies onto the screen.
+#[derive(Debug, Event)]
+pub struct BirdArrived {
+    /// Name of the bird's species.
+    pub species: String,
+}
+
+/// Sent when a bird leaves the screen, whether it was fed or not.
+///
+/// Birds that are despawned along with their level do not depart.
+#[derive(Debug, Event)]
+pub struct BirdDeparted {
+    /// Name of the bird's species.
+    pub species: String,
+    /// Whether the bird left with no hunger remaining.
+    pub fed_fully: bool,
+    /// Whether the bird left while still hungry, such as after waiting too long or being scared off,
+    /// or without having been fed at all.
+    /// Birds that were only partly fed and had moved on are neither fed fully nor escaped hungry.
+    pub escaped_hungry: bool,
+}
+
+impl BirdDeparted {
+    /// How well fed `bird` was when it left in `state`.
+    fn new(bird: &Bird, state: BirdState) -> Self {
+        let fed_fully = bird.hunger == 0;
+        let escaped_hungry = !fed_fully && (state.is_hungry() || bird.hunger >= bird.initial_hunger);
+        Self { species: bird.name.clone(), fed_fully, escaped_hungry }
+    }
+}
+
+/// Direction of the screen edge closest to `position`,
+/// for a screen of `half_size` centred on the origin.
+pub fn exit_direction(position: Vec2, half_size: Vec2) -> Vec2 {
+    let relative = position / half_size;
+    if relative.x.abs() > relative.y.abs() {
+        Vec2::new(relative.x.signum(), 0.)
+    } else {
+        Vec2::new(0., relative.y.signum())
+    }
+}
+
+/// Reports birds as they arrive on screen, then despawns them once they have flown off again,
+/// reporting how well fed they were and taking away any [BirdAsset::escape_penalty] for birds that left hungry.
+///
+/// Birds start off screen, so only count as leaving once they have been seen.
+pub(super) fn bird_departure_sys(
+    mut cmd: Commands,
+    mut birds: Query<(Entity, &mut Bird, &BirdBehaviour, &Transform, &AssetHandle<BirdAsset>)>,
+    mut arrived_evw: EventWriter<BirdArrived>,
+    mut departed_evw: EventWriter<BirdDeparted>,
+    mut score_evw: EventWriter<ScoreEvent>,
+    windows: Query<&Window>,
+    assets: Res<Assets<BirdAsset>>,
+) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let half_size = window.size() * 0.5;
+
+    for (entity, mut bird, behaviour, tf, handle) in birds.iter_mut() {
+        let Some(asset) = assets.get(&handle.0) else {
+            continue;
+        };
+        let position = tf.translation.xy().abs();
+        if position.cmple(half_size).all() {
+            if !bird.entered_screen {
+                bird.bypass_change_detection().entered_screen = true;
+                arrived_evw.write(BirdArrived { species: bird.name.clone() });
+            }
+            continue;
+        }
+
+        let margin = if bird.entered_screen { asset.size.max_element() + DEPARTURE_MARGIN } else { STRAY_MARGIN };
+        if position.cmple(half_size + margin).all() {
+            continue;
+        }
+
+        let departed = BirdDeparted::new(&bird, behaviour.state());
+        debug!("Bird {} departed, fed fully: {}, escaped hungry: {}", bird.name, departed.fed_fully, departed.escaped_hungry);
+        let escaped_hungry = departed.escaped_hungry;
+        departed_evw.write(departed);
+        if escaped_hungry && asset.escape_penalty > 0 {
+            score_evw.write(ScoreEvent {
+                reason: ScoreReason::Escaped,
+                base: -(asset.escape_penalty as i32),
+                multiplier: 1.,
+            });
+        }
+        cmd.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bird(hunger: i8) -> Bird {
+        Bird {
+            name: "pigeon".into(),
+            velocity: 100.,
+            hunger,
+            initial_hunger: 3,
+            hunger_elapsed: 0.,
+            anger: 0.,
+            drop_probability: 0.,
+            entered_screen: true,
+        }
+    }
+
+    #[test]
+    fn exits_toward_closest_edge() {
+        let half_size = Vec2::new(400., 300.);
+        assert_eq!(exit_direction(Vec2::new(350., 100.), half_size), Vec2::X);
+        assert_eq!(exit_direction(Vec2::new(-350., -100.), half_size), Vec2::NEG_X);
+        assert_eq!(exit_direction(Vec2::new(100., 250.), half_size), Vec2::Y);
+        assert_eq!(exit_direction(Vec2::new(-100., -250.), half_size), Vec2::NEG_Y);
+        // compared relative to the screen's size, so a wide screen favours the top and bottom
+        assert_eq!(exit_direction(Vec2::new(300., 240.), half_size), Vec2::Y);
+    }
+
+    #[test]
+    fn fed_bird_did_not_escape_hungry() {
+        let departed = BirdDeparted::new(&bird(0), BirdState::Leaving);
+        assert!(departed.fed_fully);
+        assert!(!departed.escaped_hungry);
+    }
+
+    #[test]
+    fn partly_fed_bird_escaped_hungry_only_while_still_hungry() {
+        let departed = BirdDeparted::new(&bird(1), BirdState::Leaving);
+        assert!(!departed.fed_fully);
+        assert!(!departed.escaped_hungry);
+
+        let departed = BirdDeparted::new(&bird(1), BirdState::Angry);
+        assert!(!departed.fed_fully);
+        assert!(departed.escaped_hungry);
+    }
+
+    #[test]
+    fn unfed_bird_escaped_hungry() {
+        for state in [BirdState::Hungry, BirdState::Fleeing, BirdState::Leaving] {
+            let departed = BirdDeparted::new(&bird(3), state);
+            assert!(!departed.fed_fully);
+            assert!(departed.escaped_hungry, "{:?}", state);
+        }
+    }
+}
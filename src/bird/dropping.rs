@@ -7,19 +7,37 @@ use rand::{
 };
 use serde::Deserialize;
 
-use super::{Bird, asset::BirdAsset};
+use super::{
+    Bird,
+    asset::BirdAsset,
+    behaviour::{BirdBehaviour, BirdState},
+};
 use crate::{
-    level::LevelRootEntity,
+    level::{Level, LevelAsset, LevelRootEntity},
     physics::{Collider, ColliderContactEvent, ColliderIntersectionMode, Velocity},
     player::Player,
-    util::{AssetHandle, AssetManagerPlugin, EntityAssetReadyEvent},
+    score::{ScoreEvent, ScoreReason},
+    status::{StatusEffects, StatusKind},
+    util::{AssetHandle, AssetManagerPlugin, EntityAssetReadyEvent, GROUND_DEPTH_BAND, YSort},
+    validate::{Validate, ValidationContext},
 };
 
+/// Downward acceleration of a falling [BirdDropping].
+const DROPPING_GRAVITY: f32 = 400.;
+/// Highest a [BirdDropping] can be above the ground and still hit a player.
+const DROPPING_CONTACT_ALTITUDE: f32 = 20.;
+/// Half the width and height of the shadow under a falling [BirdDropping].
+const DROPPING_SHADOW_SIZE: Vec2 = Vec2::new(20., 8.);
+/// Size of the shadow as a dropping starts to fall, as a fraction of its size once it lands.
+const DROPPING_SHADOW_START_SCALE: f32 = 0.3;
+/// Z translation of landed droppings in levels without a ground depth band.
+const DROPPING_GROUND_Z: f32 = 15.;
+
 /// Birds occasionally drop things.
-/// Those things move until they hit the ground.
-/// They stay on the ground for a bit until they are (depending on their type)
-/// - Picked up by the player (powerup)
-/// - Walked over by the player (poop)
+/// Those things fall from the height the birds fly at until they hit the ground,
+/// casting a shadow where they will land.
+/// They stay on the ground for a bit until they are touched by the player,
+/// who then gets the dropping's [DroppingEffect], be it a power-up or something nastier.
 pub struct BirdDroppingPlugin;
 
 impl Plugin for BirdDroppingPlugin {
@@ -32,7 +50,7 @@ impl Plugin for BirdDroppingPlugin {
                 bird_spawn_dropping_sys,
                 dropping_fall_sys,
                 dropping_decay_sys,
-                poop_dropping_player_hit_sys,
+                dropping_player_hit_sys,
             ),
         );
     }
@@ -42,86 +60,236 @@ impl Plugin for BirdDroppingPlugin {
 pub struct BirdDroppingAsset {
     sprite: PathBuf,
     decay_rate: f32,
+    /// Colour the sprite is multiplied by, to tell droppings sharing a sprite apart.
+    #[serde(default)]
+    tint: Option<LinearRgba>,
+    /// What touching the dropping does to a player.
+    #[serde(default)]
+    effect: DroppingEffect,
+}
+
+impl Validate for BirdDroppingAsset {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        ctx.check_asset_exists(&self.sprite);
+        if self.decay_rate < 0. {
+            ctx.error("decay_rate", "decay_rate must not be negative");
+        }
+        self.effect.validate(ctx);
+    }
+}
+
+/// What a dropping does to the player that touches it.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub enum DroppingEffectKind {
+    /// Takes `magnitude` health away, unless the player is [StatusKind::Invulnerable].
+    Damage,
+    /// Gives back `magnitude` health, up to the player's starting health.
+    Heal,
+    /// Scores `magnitude` points.
+    ScoreBonus,
+    /// Applies a status effect of `magnitude` for `duration`, such as a power-up.
+    Status(StatusKind),
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct DroppingEffect {
+    pub kind: DroppingEffectKind,
+    #[serde(default)]
+    pub magnitude: f32,
+    /// Seconds a [DroppingEffectKind::Status] is applied for. Not used by effects that happen at once.
+    #[serde(default)]
+    pub duration: f32,
+}
+
+impl Default for DroppingEffect {
+    fn default() -> Self {
+        Self { kind: DroppingEffectKind::Damage, magnitude: 10., duration: 0. }
+    }
+}
+
+impl Validate for DroppingEffect {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        if self.magnitude < 0. {
+            ctx.error("magnitude", "magnitude must not be negative");
+        }
+        if matches!(self.kind, DroppingEffectKind::Status(_)) && self.duration <= 0. {
+            ctx.error("duration", format!("duration must be positive for {:?}", self.kind));
+        }
+    }
 }
 
 #[derive(Debug, Component)]
 struct BirdDropping;
 
+/// Height of a falling [BirdDropping] above the ground beneath it.
+///
+/// The dropping is drawn `altitude` above its [DroppingShadow], and lands where the shadow is.
+#[derive(Debug, Component)]
+struct Falling {
+    altitude: f32,
+    /// Altitude the dropping was dropped from.
+    start_altitude: f32,
+    /// Downward speed, which grows with [DROPPING_GRAVITY].
+    fall_speed: f32,
+}
+
+/// Marks the shadow cast on the ground by a falling [BirdDropping].
 #[derive(Debug, Component)]
-struct OnGround;
+struct DroppingShadow;
 
+/// Marks things that have come to rest on the ground, such as droppings and
+/// [crate::projectile::GroundFood].
+#[derive(Debug, Component)]
+pub struct OnGround;
+
+/// Sets up droppings once their asset has loaded.
+/// Droppings that are not yet on the ground start falling from the level's flight altitude.
 fn load_dropping_sys(
     mut cmd: Commands,
     mut asset_events: EventReader<EntityAssetReadyEvent<BirdDroppingAsset>>,
+    on_ground: Query<Has<OnGround>>,
     asset_server: Res<AssetServer>,
     assets: Res<Assets<BirdDroppingAsset>>,
+    level: Res<Level>,
+    level_assets: Res<Assets<LevelAsset>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
+    let altitude = level_assets.get(&level.level_handle).map_or(0., |asset| asset.flight_altitude);
     for EntityAssetReadyEvent((entities, asset_id)) in asset_events.read() {
         let asset = assets.get(asset_id).expect("Asset should exist");
         for entity in entities {
+            let landed = on_ground.get(*entity).unwrap_or_default();
             if let Ok(mut entity) = cmd.get_entity(*entity) {
                 entity.despawn_related::<Children>().insert((
                     BirdDropping,
                     Collider::Rectangle(Rectangle::new(50., 50.)),
                     ColliderIntersectionMode::AllowAll,
-                    Sprite { image: asset_server.load(asset.sprite.clone()), ..default() },
+                    Sprite {
+                        image: asset_server.load(asset.sprite.clone()),
+                        color: asset.tint.map_or(Color::WHITE, Color::from),
+                        ..default()
+                    },
                 ));
+                if !landed {
+                    entity
+                        .insert_if_new(Falling { altitude, start_altitude: altitude, fall_speed: 0. })
+                        .with_child((
+                            DroppingShadow,
+                            Mesh2d(meshes.add(Ellipse::new(DROPPING_SHADOW_SIZE.x, DROPPING_SHADOW_SIZE.y))),
+                            MeshMaterial2d(materials.add(Color::srgba(0., 0., 0., 0.35))),
+                            Transform::from_scale(Vec3::splat(DROPPING_SHADOW_START_SCALE)),
+                        ));
+                }
             };
         }
     }
 }
 
 /// Spawns [BirdDropping] at random for each hungry [Bird] in the level.
+/// Angry birds drop things more often, and may aim them at the closest [Player]. Stunned birds drop nothing.
 fn bird_spawn_dropping_sys(
     mut cmd: Commands,
-    birds: Query<(&Bird, &Velocity, &Transform, &AssetHandle<BirdAsset>)>,
+    birds: Query<(&Bird, &BirdBehaviour, &StatusEffects, &Velocity, &Transform, &AssetHandle<BirdAsset>)>,
+    players: Query<&Transform, With<Player>>,
     level: LevelRootEntity,
     asset_server: Res<AssetServer>,
     assets: Res<Assets<BirdAsset>>,
 ) {
     let mut rng = rand::rng();
-    for (bird, velocity, tf, asset_handle) in birds.iter() {
-        if bird.hunger == 0 || !rng.random_bool(bird.drop_probability as f64) {
+    for (bird, behaviour, status, velocity, tf, asset_handle) in birds.iter() {
+        let Some(asset) = assets.get(&asset_handle.0) else {
+            continue;
+        };
+        let drop_multiplier =
+            asset.temper.as_ref().map_or(1., |temper| temper.drop_multiplier(bird.anger));
+        let drop_probability = (bird.drop_probability * drop_multiplier).clamp(0., 1.);
+        if !behaviour.state().is_hungry() || !status.can_act() || !rng.random_bool(drop_probability as f64) {
             continue;
         }
 
-        let mut dropping_tf = tf.clone();
+        let mut dropping_tf = *tf;
         dropping_tf.translation.z -= 1.;
 
-        if let Some(asset) = assets.get(&asset_handle.0)
-            && let Some(droppings) = &asset.droppings
+        let aim = asset.temper.as_ref().is_some_and(|temper| temper.aim_droppings)
+            && behaviour.state() == BirdState::Angry;
+        if aim
+            && let Some(to_player) = players
+                .iter()
+                .map(|player_tf| player_tf.translation.xy() - tf.translation.xy())
+                .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
         {
-            let dist = WeightedIndex::new(droppings.iter().map(|d| d.probability)).unwrap();
-            let dropping_index = dist.sample(&mut rng);
-
-            cmd.entity(*level).with_child((
-                dropping_tf,
-                Velocity(velocity.0),
-                AssetHandle::<BirdDroppingAsset>(
-                    asset_server.load(droppings[dropping_index].asset.clone()),
-                ),
-            ));
+            dropping_tf.rotation = Quat::from_rotation_arc_2d(Vec2::Y, to_player.normalize_or(Vec2::Y));
         }
+
+        spawn_random_dropping(&mut cmd, *level, &asset_server, asset, dropping_tf, velocity.0, &mut rng);
     }
 }
 
-/// Reduces [BirdDropping] velocities until they reach zero.
-/// The [Velocity] for spawned droppings initially matches that of the bird that 'dropped' them.
+/// Spawns one of the bird's droppings, picked at random, moving along `tf` at `speed`.
+pub(super) fn spawn_random_dropping(
+    cmd: &mut Commands,
+    level: Entity,
+    asset_server: &AssetServer,
+    asset: &BirdAsset,
+    tf: Transform,
+    speed: f32,
+    rng: &mut impl Rng,
+) {
+    if let Some(droppings) = &asset.droppings {
+        let dist = WeightedIndex::new(droppings.iter().map(|d| d.probability)).unwrap();
+        let dropping_index = dist.sample(rng);
+
+        cmd.entity(level).with_child((
+            tf,
+            Velocity(speed),
+            AssetHandle::<BirdDroppingAsset>(
+                asset_server.load(droppings[dropping_index].asset.clone()),
+            ),
+        ));
+    }
+}
+
+/// Drops falling [BirdDropping]s toward the ground under [DROPPING_GRAVITY],
+/// carrying on along the ground at the speed they were dropped at.
 ///
-/// Once this reaches zero, they are considered to be on the ground.
-/// The Z transform is set to that of the ground at the point they have stopped.
+/// Each dropping's [DroppingShadow] stays on the ground beneath it, growing as the dropping nears it.
+/// Once the dropping reaches the ground it stops where it is.
+/// The Z transform is then sorted within the [GROUND_DEPTH_BAND] if the level has one.
 fn dropping_fall_sys(
     mut cmd: Commands,
-    mut droppings: Query<(Entity, &mut Velocity, &mut Transform), With<BirdDropping>>,
+    mut droppings: Query<
+        (Entity, &mut Falling, &mut Velocity, &mut Transform, Option<&Children>),
+        With<BirdDropping>,
+    >,
+    mut shadows: Query<&mut Transform, (With<DroppingShadow>, Without<BirdDropping>)>,
+    time: Res<Time>,
 ) {
-    for (entity, mut velocity, mut tf) in droppings.iter_mut() {
-        if velocity.0 > 0. {
-            velocity.0 = (velocity.0 - 1.).max(0.);
-
-            if velocity.0 == 0. {
-                tf.translation.z = 15.; // IDK we should probably set a ground Z value somewhere shared
-                cmd.entity(entity)
-                    .insert(OnGround);
+    let delta = time.delta_secs();
+    for (entity, mut falling, mut velocity, mut tf, children) in droppings.iter_mut() {
+        falling.fall_speed += DROPPING_GRAVITY * delta;
+        let fall = (falling.fall_speed * delta).min(falling.altitude);
+        falling.altitude -= fall;
+        tf.translation.y -= fall;
+
+        if falling.altitude <= 0. {
+            velocity.0 = 0.;
+            tf.translation.z = DROPPING_GROUND_Z;
+            cmd.entity(entity)
+                .despawn_related::<Children>()
+                .remove::<Falling>()
+                .insert((OnGround, YSort::new(GROUND_DEPTH_BAND)));
+            continue;
+        }
+
+        // the shadow is a child, so undo the dropping's rotation to keep it flat on the ground
+        let progress = if falling.start_altitude > 0. { 1. - falling.altitude / falling.start_altitude } else { 1. };
+        for child in children.into_iter().flatten() {
+            if let Ok(mut shadow_tf) = shadows.get_mut(*child) {
+                let inverse = tf.rotation.inverse();
+                shadow_tf.translation = inverse * Vec3::new(0., -falling.altitude, -0.5);
+                shadow_tf.rotation = inverse;
+                shadow_tf.scale = Vec3::splat(DROPPING_SHADOW_START_SCALE.lerp(1., progress));
             }
         }
     }
@@ -147,18 +315,47 @@ fn dropping_decay_sys(
     }
 }
 
-fn poop_dropping_player_hit_sys(
+/// Applies the [DroppingEffect] of droppings that players touch once they are near the ground.
+fn dropping_player_hit_sys(
     mut cmd: Commands,
-    droppings: Query<&BirdDropping>,
-    mut players: Query<&mut Player>,
+    droppings: Query<(&AssetHandle<BirdDroppingAsset>, Option<&Falling>), With<BirdDropping>>,
+    mut players: Query<(&mut Player, &mut StatusEffects)>,
     mut contact_ev: EventReader<ColliderContactEvent>,
+    mut score_evw: EventWriter<ScoreEvent>,
+    assets: Res<Assets<BirdDroppingAsset>>,
 ) {
     for contact in contact_ev.read() {
-        if let Some((dropping, player)) = contact.between(&droppings, &players) {
-            cmd.entity(dropping).despawn();
+        let Some((dropping, player)) = contact.between(&droppings, &players) else {
+            continue;
+        };
+        let Ok((handle, falling)) = droppings.get(dropping) else {
+            continue;
+        };
+        // droppings still high above the ground pass over the player
+        if falling.is_some_and(|falling| falling.altitude > DROPPING_CONTACT_ALTITUDE) {
+            continue;
+        }
+        let effect = assets.get(&handle.0).map(|asset| asset.effect).unwrap_or_default();
+        cmd.entity(dropping).despawn();
 
-            let mut player = players.get_mut(player).unwrap();
-            player.health -= 10;
+        let (mut player, mut status) = players.get_mut(player).unwrap();
+        match effect.kind {
+            DroppingEffectKind::Damage => {
+                if !status.has(StatusKind::Invulnerable) {
+                    player.health -= effect.magnitude as i32;
+                }
+            }
+            DroppingEffectKind::Heal => {
+                player.health = (player.health + effect.magnitude as i32).min(player.max_health);
+            }
+            DroppingEffectKind::ScoreBonus => {
+                score_evw.write(ScoreEvent {
+                    reason: ScoreReason::PowerUp,
+                    base: effect.magnitude as i32,
+                    multiplier: 1.,
+                });
+            }
+            DroppingEffectKind::Status(kind) => status.apply(kind, effect.magnitude, effect.duration),
         }
     }
 }
@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    asset::BirdAsset,
+    behaviour::{BirdBehaviour, BirdState, BirdStateEvent},
+    movement::BirdFlight,
+};
+use crate::{
+    util::AssetHandle,
+    validate::{Validate, ValidationContext},
+};
+
+/// How birds of a species steer to stay together when released as a flock.
+///
+/// Each weight controls how strongly one of the boids rules pulls on the bird's heading,
+/// `0` disables the rule.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Flocking {
+    /// Distance within which other members of the flock are followed.
+    pub radius: f32,
+    /// Gap birds try to keep between each other.
+    pub spacing: f32,
+    /// Steering away from members that are closer than `spacing`.
+    pub separation: f32,
+    /// Steering toward the average heading of nearby members.
+    pub alignment: f32,
+    /// Steering toward the middle of nearby members.
+    pub cohesion: f32,
+    /// Fastest a bird turns to follow its flock, in degrees per second.
+    #[serde(default = "default_turn_rate")]
+    pub turn_rate: f32,
+}
+
+fn default_turn_rate() -> f32 {
+    90.
+}
+
+impl Validate for Flocking {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        if self.radius <= 0. {
+            ctx.error("radius", "flocking radius must be positive");
+        }
+        if self.spacing <= 0. {
+            ctx.error("spacing", "flocking spacing must be positive");
+        }
+        for (weight, name) in [
+            (self.separation, "separation"),
+            (self.alignment, "alignment"),
+            (self.cohesion, "cohesion"),
+            (self.turn_rate, "turn_rate"),
+        ] {
+            if weight < 0. {
+                ctx.error(name, format!("{} must not be negative, found {}", name, weight));
+            }
+        }
+    }
+}
+
+/// Marks birds that were released together from a spawner.
+#[derive(Debug, Component)]
+pub struct FlockMember {
+    pub flock: u32,
+}
+
+/// Steers hungry flock members using separation, alignment and cohesion.
+///
+/// Steering turns the heading that the bird's [super::movement::FlightPattern] is applied to,
+/// so members keep their pattern while following each other.
+pub(super) fn bird_flock_sys(
+    mut birds: Query<(
+        Entity,
+        &FlockMember,
+        &Transform,
+        &mut BirdFlight,
+        &BirdBehaviour,
+        &AssetHandle<BirdAsset>,
+    )>,
+    assets: Res<Assets<BirdAsset>>,
+    time: Res<Time>,
+) {
+    // positions and headings are taken before any bird is steered so the update order does not matter
+    let members: Vec<(Entity, u32, Vec2, Vec2)> = birds
+        .iter()
+        .filter(|(.., behaviour, _)| behaviour.state().is_hungry())
+        .map(|(entity, member, tf, flight, ..)| {
+            (entity, member.flock, tf.translation.xy(), flight.forward(tf))
+        })
+        .collect();
+
+    for (entity, member, tf, mut flight, behaviour, handle) in birds.iter_mut() {
+        if !behaviour.state().is_hungry() {
+            continue;
+        }
+        let Some(flocking) = assets.get(&handle.0).and_then(|asset| asset.flocking.as_ref()) else {
+            continue;
+        };
+
+        let position = tf.translation.xy();
+        let forward = flight.forward(tf);
+
+        let mut separation = Vec2::ZERO;
+        let mut heading_sum = Vec2::ZERO;
+        let mut position_sum = Vec2::ZERO;
+        let mut neighbours = 0;
+        for (other, flock, other_position, other_forward) in &members {
+            let offset = position - *other_position;
+            let distance = offset.length();
+            if *other == entity || *flock != member.flock || distance > flocking.radius {
+                continue;
+            }
+
+            if distance < flocking.spacing {
+                separation += offset.normalize_or_zero() * (1. - distance / flocking.spacing);
+            }
+            heading_sum += *other_forward;
+            position_sum += *other_position;
+            neighbours += 1;
+        }
+        if neighbours == 0 {
+            continue;
+        }
+
+        let alignment = (heading_sum / neighbours as f32).normalize_or_zero();
+        let cohesion = (position_sum / neighbours as f32 - position) / flocking.radius;
+        let desired = forward
+            + separation * flocking.separation
+            + alignment * flocking.alignment
+            + cohesion * flocking.cohesion;
+
+        let max_turn = flocking.turn_rate.to_radians() * time.delta_secs();
+        flight.steer(forward.angle_to(desired).clamp(-max_turn, max_turn));
+    }
+}
+
+/// Breaks up a flock when one of its members is fed, scaring off the rest.
+pub(super) fn flock_scatter_sys(
+    mut cmd: Commands,
+    mut state_events: ParamSet<(EventReader<BirdStateEvent>, EventWriter<BirdStateEvent>)>,
+    mut birds: Query<(Entity, &FlockMember, &mut BirdBehaviour)>,
+    time: Res<Time>,
+) {
+    let scattered: Vec<u32> = state_events
+        .p0()
+        .read()
+        .filter(|evt| evt.to == BirdState::Eating)
+        .filter_map(|evt| birds.get(evt.bird).ok().map(|(_, member, _)| member.flock))
+        .collect();
+    if scattered.is_empty() {
+        return;
+    }
+
+    let now = time.elapsed_secs();
+    for (entity, member, mut behaviour) in birds.iter_mut() {
+        if !scattered.contains(&member.flock) {
+            continue;
+        }
+
+        cmd.entity(entity).remove::<FlockMember>();
+        if behaviour.state().is_hungry()
+            && let Some(evt) = behaviour.transition(entity, BirdState::Fleeing, now)
+        {
+            state_events.p1().write(evt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        bird::movement::{FlightPattern, bird_flight_sys},
+        physics::Velocity,
+    };
+
+    #[test]
+    fn straight_flock_members_turn_toward_each_other() {
+        let mut world = World::new();
+        let mut assets = Assets::<BirdAsset>::default();
+        let handle = assets.add(
+            ron::de::from_str::<BirdAsset>(include_str!("../../assets/birds/canada_goose.ron"))
+                .unwrap(),
+        );
+        world.insert_resource(assets);
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_millis(100));
+        world.insert_resource(time);
+
+        // both flying up the screen, too far apart for separation to push them away
+        let birds = [-100., 100.].map(|x| {
+            world
+                .spawn((
+                    FlockMember { flock: 1 },
+                    Transform::from_xyz(x, 0., 0.),
+                    BirdFlight::new(FlightPattern::Straight, 100.),
+                    Velocity(100.),
+                    BirdBehaviour::new(0.),
+                    AssetHandle(handle.clone()),
+                ))
+                .id()
+        });
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((bird_flock_sys, bird_flight_sys).chain());
+        schedule.run(&mut world);
+
+        let forward = |bird| (world.get::<Transform>(bird).unwrap().rotation * Vec3::Y).xy();
+        assert!(forward(birds[0]).x > 0.01, "{:?}", forward(birds[0]));
+        assert!(forward(birds[1]).x < -0.01, "{:?}", forward(birds[1]));
+    }
+}
@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    Bird,
+    PestFedEvent,
+    asset::{BirdAsset, WALK_CLIP},
+    behaviour::{BirdBehaviour, BirdState, BirdStateEvent},
+    feed_bird,
+};
+use crate::{
+    physics::Velocity,
+    projectile::GroundFood,
+    score::FeedEvent,
+    status::StatusEffects,
+    util::{AssetHandle, SpriteAnimation},
+    validate::{Validate, ValidationContext},
+};
+
+/// How close a bird has to get to [GroundFood] to eat it.
+const EAT_DISTANCE: f32 = 10.;
+
+/// How a bird goes after food lying on the ground.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Foraging {
+    /// Distance within which a hungry bird notices food on the ground.
+    pub range: f32,
+    /// Distance from the food at which the bird lands and walks the rest of the way.
+    #[serde(default = "default_landing_distance")]
+    pub landing_distance: f32,
+    /// Speed the bird walks at once it has landed.
+    #[serde(default = "default_walk_speed")]
+    pub walk_speed: f32,
+}
+
+fn default_landing_distance() -> f32 {
+    80.
+}
+
+fn default_walk_speed() -> f32 {
+    40.
+}
+
+impl Validate for Foraging {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        if self.range <= 0. {
+            ctx.error("range", "foraging range must be positive");
+        }
+        if self.landing_distance < 0. {
+            ctx.error("landing_distance", "landing_distance must not be negative");
+        }
+        if self.walk_speed <= 0. {
+            ctx.error("walk_speed", "walk_speed must be positive");
+        }
+    }
+}
+
+/// The [GroundFood] a [BirdState::Foraging] bird is heading for.
+#[derive(Debug, Component)]
+pub struct ForageTarget {
+    food: Entity,
+    /// Whether the bird has touched down and is walking to the food.
+    landed: bool,
+}
+
+/// Sends hungry birds that notice food on the ground after it, and feeds them once they reach it.
+///
+/// Birds give up if the food is eaten by another bird or fades away first.
+pub(super) fn bird_forage_sys(
+    mut cmd: Commands,
+    mut birds: Query<(
+        Entity,
+        &mut Bird,
+        &mut BirdBehaviour,
+        &mut StatusEffects,
+        &Transform,
+        Option<&ForageTarget>,
+        &AssetHandle<BirdAsset>,
+    )>,
+    ground_food: Query<(Entity, &GroundFood, &Transform)>,
+    mut state_evw: EventWriter<BirdStateEvent>,
+    mut feed_evw: EventWriter<FeedEvent>,
+    mut pest_fed_evw: EventWriter<PestFedEvent>,
+    assets: Res<Assets<BirdAsset>>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs();
+    for (entity, mut bird, mut behaviour, mut status, tf, target, handle) in birds.iter_mut() {
+        let Some(asset) = assets.get(&handle.0) else {
+            continue;
+        };
+        let position = tf.translation.xy();
+
+        let Some(target) = target else {
+            let Some(foraging) = &asset.foraging else {
+                continue;
+            };
+            if !matches!(behaviour.state(), BirdState::Hungry | BirdState::Circling) {
+                continue;
+            }
+            let closest = ground_food
+                .iter()
+                .filter(|(_, food, _)| asset.feeding(food.food).is_some())
+                .map(|(food, _, food_tf)| (food, food_tf.translation.xy().distance(position)))
+                .filter(|(_, distance)| *distance <= foraging.range)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((food, _)) = closest
+                && let Some(evt) = behaviour.transition(entity, BirdState::Foraging, now)
+            {
+                cmd.entity(entity).insert(ForageTarget { food, landed: false });
+                state_evw.write(evt);
+            }
+            continue;
+        };
+
+        // scared off, fed by the player or gave up
+        if behaviour.state() != BirdState::Foraging {
+            cmd.entity(entity).remove::<ForageTarget>();
+            continue;
+        }
+
+        let Ok((food_entity, food, food_tf)) = ground_food.get(target.food) else {
+            if let Some(evt) = behaviour.transition(entity, BirdState::Hungry, now) {
+                state_evw.write(evt);
+            }
+            continue;
+        };
+        if food_tf.translation.xy().distance(position) > EAT_DISTANCE || !status.can_act() {
+            continue;
+        }
+
+        if feed_bird(
+            &mut bird,
+            &mut status,
+            asset,
+            &handle.0,
+            food.food,
+            &mut feed_evw,
+            &mut pest_fed_evw,
+        ) {
+            cmd.entity(food_entity).despawn();
+            if let Some(evt) = behaviour.transition(entity, BirdState::Eating, now) {
+                state_evw.write(evt);
+            }
+        }
+    }
+}
+
+/// Steers foraging birds toward their food, landing and walking once they are close.
+///
+/// Runs after [super::movement::bird_flight_sys] so the bird's flight does not undo the steering.
+pub(super) fn bird_walk_sys(
+    mut birds: Query<(
+        &mut ForageTarget,
+        &BirdBehaviour,
+        &mut Transform,
+        &mut Velocity,
+        &mut SpriteAnimation,
+        &StatusEffects,
+        &AssetHandle<BirdAsset>,
+    )>,
+    ground_food: Query<&Transform, (With<GroundFood>, Without<ForageTarget>)>,
+    assets: Res<Assets<BirdAsset>>,
+) {
+    for (mut target, behaviour, mut tf, mut velocity, mut animation, status, handle) in birds.iter_mut() {
+        if behaviour.state() != BirdState::Foraging {
+            continue;
+        }
+        let Some(asset) = assets.get(&handle.0) else {
+            continue;
+        };
+        let Some(foraging) = &asset.foraging else {
+            continue;
+        };
+        let Ok(food_tf) = ground_food.get(target.food) else {
+            continue;
+        };
+
+        let to_food = food_tf.translation.xy() - tf.translation.xy();
+        if to_food.length() <= EAT_DISTANCE {
+            velocity.0 = 0.;
+            continue;
+        }
+
+        // turn in world space to keep the bird's flip
+        let forward = (tf.rotation * Vec3::Y).xy();
+        tf.rotation = Quat::from_rotation_z(forward.angle_to(to_food)) * tf.rotation;
+
+        if !target.landed && to_food.length() <= foraging.landing_distance {
+            target.landed = true;
+            animation.play(asset.clip(Some(WALK_CLIP)));
+        }
+        if target.landed {
+            velocity.0 = foraging.walk_speed * status.speed_multiplier();
+        }
+    }
+}
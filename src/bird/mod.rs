@@ -1,20 +1,41 @@
 pub mod asset;
+pub mod avoidance;
+pub mod behaviour;
+pub mod boss;
+pub mod departure;
 pub mod dropping;
+pub mod flocking;
+pub mod forage;
+pub mod movement;
+pub mod perch;
+pub mod pest;
 pub mod spawner;
+pub mod temper;
 
 use std::path::PathBuf;
 
 use asset::*;
+use avoidance::*;
+use behaviour::*;
+use boss::*;
 use bevy::prelude::*;
-use rand::Rng;
+use departure::*;
+use flocking::*;
+use forage::*;
+use movement::*;
+use perch::*;
+use pest::*;
 use spawner::*;
+use temper::*;
 
 use crate::{
     GameState,
-    level::Level,
-    physics::{ColliderContactEvent, Velocity},
-    projectile::Projectile,
-    util::{AssetManagerPlugin, TargetTransform, animate_sys},
+    physics::ColliderContactEvent,
+    player::Player,
+    projectile::{Food, Projectile},
+    score::FeedEvent,
+    status::StatusEffects,
+    util::{AssetHandle, AssetManagerPlugin, animate_sys},
 };
 
 pub struct BirdPlugin;
@@ -22,14 +43,39 @@ pub struct BirdPlugin;
 impl Plugin for BirdPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((AssetManagerPlugin::<BirdAsset>::default(), dropping::BirdDroppingPlugin));
+        app.add_event::<BirdStateEvent>();
+        app.add_event::<BirdArrived>();
+        app.add_event::<BirdDeparted>();
+        app.add_event::<PestFedEvent>();
         app.add_systems(OnEnter(GameState::Game), setup_sys);
+        app.add_systems(OnEnter(GameState::GameOver), despawn_boss_hud_sys);
+        app.add_systems(OnEnter(GameState::Menu), despawn_boss_hud_sys);
         app.add_systems(FixedUpdate, setup_spawner_sys.run_if(in_state(GameState::Game)));
         app.add_systems(
             FixedUpdate,
             (
                 animate_sys,
                 bird_spawn_sys,
-                bird_hit_sys,
+                bird_telegraph_sys,
+                (
+                    bird_hit_sys.in_set(BirdFeedSet),
+                    bird_scare_sys,
+                    bird_deter_sys,
+                    bird_forage_sys,
+                    bird_perch_sys,
+                    bird_temper_sys,
+                    bird_state_timeout_sys,
+                    flock_scatter_sys,
+                    enter_bird_state_sys,
+                    boss_phase_sys,
+                )
+                    .chain(),
+                boss_barrage_sys,
+                pest_fed_sys,
+                update_boss_hud_sys,
+                bird_wave_sys,
+                (bird_flock_sys, bird_avoid_sys, bird_flight_sys, bird_walk_sys, bird_perch_flight_sys).chain(),
+                bird_departure_sys,
                 update_bird_tweet_sys,
                 setup_bird_hunger_bar_sys,
                 update_bird_hunger_bar_sys,
@@ -40,16 +86,26 @@ impl Plugin for BirdPlugin {
     }
 }
 
+/// Systems that feed birds projectiles that hit them, despawning the projectiles that are eaten.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct BirdFeedSet;
+
 #[derive(Component)]
 struct Bird {
     name: String,
+    /// Speed the bird's movement is based on, see [BirdAsset::velocity] and [crate::level::BirdTweaks]
+    velocity: f32,
     /// Units of food required to satisfy hunger
     hunger: i8,
     initial_hunger: i8,
-    /// Base points to grant player on being fed unit of food
-    on_feed_points: u32,
+    /// Seconds since hunger last grew, see [BirdTemper::hunger_interval]
+    hunger_elapsed: f32,
+    /// How fed up the bird is with being ignored, from 0 to 1
+    anger: f32,
     /// Probability that on each update the bird will drop something
     drop_probability: f32,
+    /// Whether the bird has made it onto the screen yet, see [bird_departure_sys]
+    entered_screen: bool,
 }
 
 impl Bird {
@@ -61,58 +117,110 @@ impl Bird {
 #[derive(Component)]
 struct BirdHungerBar;
 
-/// Makes birds fly away from non bird entities that collide with them.
+/// Feeds hungry birds that collide with projectiles, depending on what food the bird will eat.
+/// Birds that are already eating, have had their fill or are stunned let projectiles pass through.
 fn bird_hit_sys(
     mut cmd: Commands,
     mut contact_ev: EventReader<ColliderContactEvent>,
-    mut birds: Query<(&mut Velocity, &Transform, &mut TargetTransform, &mut Bird)>,
-    mut projectiles: Query<(Entity, &Projectile)>,
-    mut level: ResMut<Level>,
+    mut birds: Query<(&mut Bird, &mut BirdBehaviour, &mut StatusEffects, &AssetHandle<BirdAsset>)>,
+    projectiles: Query<&Projectile>,
+    mut state_evw: EventWriter<BirdStateEvent>,
+    mut feed_evw: EventWriter<FeedEvent>,
+    mut pest_fed_evw: EventWriter<PestFedEvent>,
+    assets: Res<Assets<BirdAsset>>,
+    time: Res<Time>,
 ) {
-    let mut rng = rand::rng();
     for ev in contact_ev.read() {
-        // collision must be between a bird...
-        let bird = if let Ok(b) = birds.get_mut(ev.a) {
-            Some(b)
-        }
-        else if let Ok(b) = birds.get_mut(ev.b) {
-            Some(b)
-        }
-        else {
-            None
+        let Some((bird_entity, projectile_entity)) = ev.between(&birds, &projectiles) else {
+            continue;
+        };
+        let Ok((mut bird, mut behaviour, mut status, handle)) = birds.get_mut(bird_entity) else {
+            continue;
         };
-        // ...and a projectile
-        let projectile = if let Ok(b) = projectiles.get_mut(ev.a) {
-            Some(b)
+        let Ok(projectile) = projectiles.get(projectile_entity) else {
+            continue;
+        };
+        let Some(asset) = assets.get(&handle.0) else {
+            continue;
+        };
+        if !behaviour.state().is_hungry() || !status.can_act() {
+            continue;
         }
-        else if let Ok(b) = projectiles.get_mut(ev.b) {
-            Some(b)
+        // refused food carries on past the bird
+        if !feed_bird(
+            &mut bird,
+            &mut status,
+            asset,
+            &handle.0,
+            projectile.food,
+            &mut feed_evw,
+            &mut pest_fed_evw,
+        ) {
+            continue;
         }
-        else {
-            None
-        };
+        cmd.entity(projectile_entity).despawn();
 
-        // if we have both then we're good :D
-        if let Some((
-            (mut velocity, tf, mut target_tf, mut bird),
-            (projectile_entity, _), // the projectile
-        )) = bird.zip(projectile)
-        {
-            if bird.hunger == 0 {
-                continue;
-            }
-            bird.hunger = bird.hunger.saturating_sub(1);
-            level.score += bird.on_feed_points;
-            cmd.entity(projectile_entity).despawn();
+        if let Some(evt) = behaviour.transition(bird_entity, BirdState::Eating, time.elapsed_secs()) {
+            state_evw.write(evt);
+        }
+    }
+}
 
-            // fly away once no longer hungry
-            if bird.hunger == 0 {
-                velocity.0 *= 2.;
+/// Feeds `food` to a hungry bird, returning `false` if the bird refuses it.
+///
+/// What feeding is worth depends on the bird's [Disposition].
+/// Foods with a [crate::status::StatusSpec] apply it to the bird.
+fn feed_bird(
+    bird: &mut Bird,
+    status: &mut StatusEffects,
+    asset: &BirdAsset,
+    handle: &Handle<BirdAsset>,
+    food: Food,
+    feed_evw: &mut EventWriter<FeedEvent>,
+    pest_fed_evw: &mut EventWriter<PestFedEvent>,
+) -> bool {
+    let Some(feeding) = asset.feeding(food) else {
+        return false;
+    };
 
-                let mut new_target_tf = tf.clone();
-                new_target_tf.rotate_local_z(if rng.random_bool(0.5) { -2. } else { 2. });
-                target_tf.update(new_target_tf);
-            }
+    bird.hunger = bird.hunger.saturating_sub(feeding.hunger).max(0);
+    if let Some(temper) = &asset.temper {
+        bird.anger = (bird.anger - temper.calm_on_feed).max(0.);
+    }
+    if let Some(spec) = &feeding.status {
+        status.apply_spec(spec);
+    }
+    match asset.disposition {
+        Disposition::Friendly => {
+            feed_evw.write(FeedEvent { points: feeding.points, fully_fed: bird.hunger == 0 });
+        }
+        Disposition::Neutral => (),
+        Disposition::Pest { penalty, attracts } => {
+            pest_fed_evw.write(PestFedEvent { pest: handle.clone(), penalty, attracts });
+        }
+    }
+    true
+}
+
+/// Scares off birds that bump into a player.
+fn bird_scare_sys(
+    mut contact_ev: EventReader<ColliderContactEvent>,
+    mut birds: Query<&mut BirdBehaviour>,
+    players: Query<(), With<Player>>,
+    mut state_evw: EventWriter<BirdStateEvent>,
+    time: Res<Time>,
+) {
+    for ev in contact_ev.read() {
+        let Some((bird_entity, _)) = ev.between(&birds, &players) else {
+            continue;
+        };
+        let Ok(mut behaviour) = birds.get_mut(bird_entity) else {
+            continue;
+        };
+        if behaviour.state().is_hungry()
+            && let Some(evt) = behaviour.transition(bird_entity, BirdState::Fleeing, time.elapsed_secs())
+        {
+            state_evw.write(evt);
         }
     }
 }
@@ -120,15 +228,17 @@ fn bird_hit_sys(
 fn update_bird_tweet_sys(
     mut bird_text: Single<&mut Text, With<BirdTweetText>>,
     birds: Query<&Bird, Added<Bird>>,
-    level: Res<Level>,
 ) {
     for bird in birds.iter() {
-        bird_text.0 = format!("Score {}\ntweet i am a {}", level.score, bird.name);
+        bird_text.0 = format!("tweet i am a {}", bird.name);
     }
 }
 
 fn update_bird_hunger_bar_sys(
-    changed_birds: Query<(&Bird, &Children), Changed<Bird>>,
+    changed_birds: Query<
+        (&Bird, &BirdBehaviour, &Children),
+        Or<(Changed<Bird>, Changed<BirdBehaviour>)>,
+    >,
     mut cmd: Commands,
     mut bird_hunger_bars: Query<
         (Entity, &mut Mesh2d, &mut MeshMaterial2d<ColorMaterial>),
@@ -137,21 +247,29 @@ fn update_bird_hunger_bar_sys(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    for (bird, children) in changed_birds.iter() {
+    for (bird, behaviour, children) in changed_birds.iter() {
         let percent_full =
             (bird.initial_hunger - bird.hunger) as f32 / (bird.initial_hunger as f32);
 
         for child in children.iter() {
             if let Ok((entity, mut mesh, mut material)) = bird_hunger_bars.get_mut(child) {
-                if bird.hunger == 0 {
-                    cmd.entity(entity).despawn()
+                if !behaviour.state().shows_hunger() {
+                    cmd.entity(entity).despawn();
+                    continue;
                 }
 
                 // fade colour bar between orange and green as bird gets fed
+                // and toward red as it gets angry
                 let orange = bevy::color::palettes::css::ORANGE;
                 let green = bevy::color::palettes::css::GREEN;
-                let colour = Srgba::from_vec3(orange.to_vec3().lerp(green.to_vec3(), percent_full));
-                material.0 = materials.add(Color::Srgba(colour));
+                let red = bevy::color::palettes::css::RED;
+                let colour = orange.to_vec3().lerp(green.to_vec3(), percent_full);
+                let colour = Srgba::from_vec3(colour.lerp(red.to_vec3(), bird.anger));
+                // recolour the bar's own material once it has one rather than adding a new one each time
+                match materials.get_mut(&material.0) {
+                    Some(existing) if material.0 != Handle::default() => existing.color = Color::Srgba(colour),
+                    _ => material.0 = materials.add(Color::Srgba(colour)),
+                }
 
                 // make colour bar bigger
                 // TODO: scale existing meshes rather than make new ones
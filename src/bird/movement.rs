@@ -0,0 +1,203 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::perch::Perched;
+use crate::{
+    physics::Velocity,
+    player::Player,
+    status::StatusEffects,
+    validate::{Validate, ValidationContext},
+};
+
+/// How a bird moves while in a [super::behaviour::BirdState].
+///
+/// Angles are given in degrees and times in seconds.
+/// Patterns steer the bird by changing its heading and [Velocity],
+/// the actual movement is left to the physics plugin.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub enum FlightPattern {
+    /// Flies in a straight line along the heading of its spawner.
+    #[default]
+    Straight,
+    /// Sways from side to side.
+    Weave { amplitude: f32, frequency: f32 },
+    /// Arcs out to alternating sides every `period`, speeding up through each arc.
+    Swoop { period: f32, angle: f32, speed_multiplier: f32 },
+    /// Flies straight for `delay`, circles `laps` times, then carries on.
+    /// Fractional laps leave on a different heading to the one it arrived on.
+    Circle { delay: f32, radius: f32, laps: f32 },
+    /// Flies straight for `delay` then stops in place for `duration`.
+    Hover { delay: f32, duration: f32 },
+    /// Turns toward and speeds up at the closest [Player] once they come within `range`.
+    /// Stops chasing once it has flown past them.
+    Dive { range: f32, speed_multiplier: f32, turn_rate: f32 },
+}
+
+impl Validate for FlightPattern {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        let positive = |ctx: &mut ValidationContext, value: f32, name: &str| {
+            if value <= 0. {
+                ctx.error(name, format!("{} must be positive, found {}", name, value));
+            }
+        };
+        let not_negative = |ctx: &mut ValidationContext, value: f32, name: &str| {
+            if value < 0. {
+                ctx.error(name, format!("{} must not be negative, found {}", name, value));
+            }
+        };
+
+        match *self {
+            FlightPattern::Straight => (),
+            FlightPattern::Weave { frequency, .. } => not_negative(ctx, frequency, "frequency"),
+            FlightPattern::Swoop { period, speed_multiplier, .. } => {
+                positive(ctx, period, "period");
+                not_negative(ctx, speed_multiplier, "speed_multiplier");
+            }
+            FlightPattern::Circle { delay, radius, laps } => {
+                not_negative(ctx, delay, "delay");
+                positive(ctx, radius, "radius");
+                not_negative(ctx, laps, "laps");
+            }
+            FlightPattern::Hover { delay, duration } => {
+                not_negative(ctx, delay, "delay");
+                not_negative(ctx, duration, "duration");
+            }
+            FlightPattern::Dive { range, speed_multiplier, turn_rate } => {
+                positive(ctx, range, "range");
+                not_negative(ctx, speed_multiplier, "speed_multiplier");
+                not_negative(ctx, turn_rate, "turn_rate");
+            }
+        }
+    }
+}
+
+/// Runtime state for a bird following a [FlightPattern].
+#[derive(Debug, Component)]
+pub struct BirdFlight {
+    pattern: FlightPattern,
+    /// Speed the bird flies at without the pattern applied.
+    pub speed: f32,
+    /// Heading the pattern is applied relative to, taken from the bird on its first update.
+    heading: Option<Quat>,
+    /// Turn asked for by [Self::steer], in radians, applied to the heading on the next update.
+    steering: f32,
+    elapsed: f32,
+    dive: DiveState,
+}
+
+#[derive(Debug, Default, PartialEq)]
+enum DiveState {
+    #[default]
+    Waiting,
+    Diving,
+    Done,
+}
+
+impl BirdFlight {
+    pub fn new(pattern: FlightPattern, speed: f32) -> Self {
+        Self { pattern, speed, heading: None, steering: 0., elapsed: 0., dive: default() }
+    }
+
+    /// Starts following `pattern` from the bird's current heading.
+    pub fn set_pattern(&mut self, pattern: FlightPattern, speed: f32) {
+        *self = Self::new(pattern, speed);
+    }
+
+    /// Changes the pattern and speed after the bird's asset has been reloaded.
+    /// An unchanged pattern carries on from where it was, keeping the bird's heading.
+    pub fn reload(&mut self, pattern: FlightPattern, speed: f32) {
+        if self.pattern != pattern {
+            let heading = self.heading;
+            *self = Self::new(pattern, speed);
+            self.heading = heading;
+        }
+        self.speed = speed;
+    }
+
+    /// Direction the pattern is being applied along.
+    /// Falls back to the way the bird is facing before its first update.
+    pub fn forward(&self, tf: &Transform) -> Vec2 {
+        (self.heading.unwrap_or(tf.rotation) * Vec3::Y).xy()
+    }
+
+    /// Turns the heading by `angle` radians in world space on the next update, whatever the pattern.
+    pub fn steer(&mut self, angle: f32) {
+        self.steering += angle;
+    }
+}
+
+/// Moves birds according to their [FlightPattern], at a speed changed by their [StatusEffects].
+/// Birds sitting on a perch stay put.
+pub(super) fn bird_flight_sys(
+    mut birds: Query<(&mut BirdFlight, &mut Transform, &mut Velocity, Option<&StatusEffects>), Without<Perched>>,
+    players: Query<&Transform, (With<Player>, Without<BirdFlight>)>,
+    time: Res<Time>,
+) {
+    for (mut flight, mut tf, mut velocity, status) in birds.iter_mut() {
+        let delta = time.delta_secs();
+        flight.elapsed += delta;
+        let t = flight.elapsed;
+        let speed = flight.speed;
+        // straight flight follows whatever else has turned the bird, such as a TargetTransform
+        let mut heading = match flight.pattern {
+            FlightPattern::Straight => tf.rotation,
+            _ => flight.heading.unwrap_or(tf.rotation),
+        };
+        heading = Quat::from_rotation_z(std::mem::take(&mut flight.steering)) * heading;
+        flight.heading = Some(heading);
+
+        // Angle is relative to the heading, in world space.
+        let (angle, speed_multiplier) = match flight.pattern {
+            FlightPattern::Straight => (0., 1.),
+            FlightPattern::Weave { amplitude, frequency } => {
+                (amplitude.to_radians() * (TAU * frequency * t).sin(), 1.)
+            }
+            FlightPattern::Swoop { period, angle, speed_multiplier } => {
+                let progress = (t % period) / period;
+                let side = if ((t / period) as u32).is_multiple_of(2) { 1. } else { -1. };
+                let arc = (PI * progress).sin();
+                (side * angle.to_radians() * arc, 1. + (speed_multiplier - 1.) * arc)
+            }
+            FlightPattern::Circle { delay, radius, laps } => {
+                let turned = ((t - delay).max(0.) * speed / radius).min(laps * TAU);
+                (turned, 1.)
+            }
+            FlightPattern::Hover { delay, duration } => {
+                let hovering = (delay..delay + duration).contains(&t);
+                (0., if hovering { 0. } else { 1. })
+            }
+            FlightPattern::Dive { range, speed_multiplier, turn_rate } => {
+                let position = tf.translation.xy();
+                let forward = (tf.rotation * Vec3::Y).xy();
+                let closest = players
+                    .iter()
+                    .map(|player_tf| player_tf.translation.xy() - position)
+                    .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+
+                match (&flight.dive, closest) {
+                    (DiveState::Waiting, Some(to_player))
+                        if to_player.length() < range && forward.dot(to_player) > 0. =>
+                    {
+                        flight.dive = DiveState::Diving;
+                    }
+                    (DiveState::Diving, Some(to_player)) if forward.dot(to_player) > 0. => {
+                        // dive is steered by permanently changing the heading
+                        let max_turn = turn_rate.to_radians() * delta;
+                        let turn = forward.angle_to(to_player).clamp(-max_turn, max_turn);
+                        heading = Quat::from_rotation_z(turn) * heading;
+                        flight.heading = Some(heading);
+                    }
+                    (DiveState::Diving, _) => flight.dive = DiveState::Done,
+                    _ => (),
+                }
+
+                (0., if flight.dive == DiveState::Diving { speed_multiplier } else { 1. })
+            }
+        };
+
+        tf.rotation = Quat::from_rotation_z(angle) * heading;
+        velocity.0 = speed * speed_multiplier * status.map_or(1., StatusEffects::speed_multiplier);
+    }
+}
@@ -0,0 +1,152 @@
+use bevy::{platform::collections::HashSet, prelude::*};
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{
+    asset::BirdAsset,
+    behaviour::{BirdBehaviour, BirdState, BirdStateEvent},
+};
+use crate::{
+    util::AssetHandle,
+    validate::{Validate, ValidationContext},
+};
+
+/// How close a bird has to get to its [Perch] to land on it.
+const LAND_DISTANCE: f32 = 8.;
+
+/// How often a bird stops to rest on scenery.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Perching {
+    /// Distance within which a hungry bird notices a free [Perch].
+    pub range: f32,
+    /// Probability that on each update a hungry bird heads for a perch.
+    pub probability: f32,
+}
+
+impl Validate for Perching {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        if self.range <= 0. {
+            ctx.error("range", "perching range must be positive");
+        }
+        ctx.check_probability(self.probability, "probability");
+    }
+}
+
+/// Point on a piece of scenery that a bird can land on, see [crate::level::LevelScenery].
+#[derive(Debug, Component)]
+pub struct Perch;
+
+/// The [Perch] a bird is heading for or sitting on.
+/// Only one bird can claim a perch at a time.
+#[derive(Debug, Component)]
+pub struct PerchTarget {
+    perch: Entity,
+}
+
+/// Marks birds sitting on a [Perch], which are left out of [super::movement::bird_flight_sys].
+#[derive(Debug, Component)]
+pub struct Perched;
+
+/// Sends hungry birds to free perches now and again, and lands them once they get there.
+///
+/// Birds leave their perch whenever they change to any other state, such as being fed or timing out.
+pub(super) fn bird_perch_sys(
+    mut cmd: Commands,
+    mut birds: Query<(
+        Entity,
+        &mut BirdBehaviour,
+        &mut Transform,
+        Option<&PerchTarget>,
+        &AssetHandle<BirdAsset>,
+    )>,
+    perches: Query<(Entity, &Transform), (With<Perch>, Without<BirdBehaviour>)>,
+    mut state_evw: EventWriter<BirdStateEvent>,
+    assets: Res<Assets<BirdAsset>>,
+    time: Res<Time>,
+) {
+    let mut rng = rand::rng();
+    let now = time.elapsed_secs();
+    let mut claimed: HashSet<Entity> = birds
+        .iter()
+        .filter_map(|(_, _, _, target, _)| target.map(|target| target.perch))
+        .collect();
+
+    for (entity, mut behaviour, mut tf, target, handle) in birds.iter_mut() {
+        let Some(target) = target else {
+            let Some(perching) = assets.get(&handle.0).and_then(|asset| asset.perching.as_ref()) else {
+                continue;
+            };
+            if behaviour.state() != BirdState::Hungry || !rng.random_bool(perching.probability as f64) {
+                continue;
+            }
+            let position = tf.translation.xy();
+            let closest = perches
+                .iter()
+                .filter(|(perch, _)| !claimed.contains(perch))
+                .map(|(perch, perch_tf)| (perch, perch_tf.translation.xy().distance(position)))
+                .filter(|(_, distance)| *distance <= perching.range)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((perch, _)) = closest
+                && let Some(evt) = behaviour.transition(entity, BirdState::Perching, now)
+            {
+                claimed.insert(perch);
+                cmd.entity(entity).insert(PerchTarget { perch });
+                state_evw.write(evt);
+            }
+            continue;
+        };
+
+        match behaviour.state() {
+            BirdState::Perching => {
+                let Ok((_, perch_tf)) = perches.get(target.perch) else {
+                    continue;
+                };
+                let to_perch = perch_tf.translation.xy() - tf.translation.xy();
+                if to_perch.length() > LAND_DISTANCE {
+                    continue;
+                }
+
+                tf.translation.x = perch_tf.translation.x;
+                tf.translation.y = perch_tf.translation.y;
+                // sit level, turning in world space to keep the bird's flip
+                let forward = (tf.rotation * Vec3::Y).xy();
+                let level = Vec2::new(if forward.x < 0. { -1. } else { 1. }, 0.);
+                tf.rotation = Quat::from_rotation_z(forward.angle_to(level)) * tf.rotation;
+
+                if let Some(evt) = behaviour.transition(entity, BirdState::Perched, now) {
+                    cmd.entity(entity).insert(Perched);
+                    state_evw.write(evt);
+                }
+            }
+            BirdState::Perched => (),
+            // fed, scared off or taking off again
+            _ => {
+                cmd.entity(entity).remove::<(PerchTarget, Perched)>();
+            }
+        }
+    }
+}
+
+/// Steers birds toward the [Perch] they are heading for.
+///
+/// Runs after [super::movement::bird_flight_sys] so the bird's flight does not undo the steering.
+pub(super) fn bird_perch_flight_sys(
+    mut birds: Query<(&PerchTarget, &BirdBehaviour, &mut Transform)>,
+    perches: Query<&Transform, (With<Perch>, Without<PerchTarget>)>,
+) {
+    for (target, behaviour, mut tf) in birds.iter_mut() {
+        if behaviour.state() != BirdState::Perching {
+            continue;
+        }
+        let Ok(perch_tf) = perches.get(target.perch) else {
+            continue;
+        };
+
+        let to_perch = perch_tf.translation.xy() - tf.translation.xy();
+        if to_perch.length() > LAND_DISTANCE {
+            // turn in world space to keep the bird's flip
+            let forward = (tf.rotation * Vec3::Y).xy();
+            tf.rotation = Quat::from_rotation_z(forward.angle_to(to_perch)) * tf.rotation;
+        }
+    }
+}
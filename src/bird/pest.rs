@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{
+    asset::BirdAsset,
+    behaviour::{BirdBehaviour, BirdState, BirdStateEvent},
+    spawner::BirdSpawner,
+};
+use crate::{
+    level::LevelRootEntity,
+    player::DeterEvent,
+    score::{ScoreEvent, ScoreReason},
+    util::AssetHandle,
+};
+
+/// How the player should treat a bird.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum Disposition {
+    /// Worth points when fed.
+    #[default]
+    Friendly,
+    /// Eats, but is not worth anything.
+    Neutral,
+    /// Costs points when fed and brings more of its kind, so is best shooed away with a
+    /// [crate::player::Deterrent].
+    Pest {
+        /// Points lost each time the bird is fed.
+        penalty: u32,
+        /// Birds of the same kind drawn in each time the bird is fed.
+        #[serde(default)]
+        attracts: u32,
+    },
+}
+
+/// Sent when a [Disposition::Pest] has been fed.
+#[derive(Debug, Event)]
+pub struct PestFedEvent {
+    pub pest: Handle<BirdAsset>,
+    pub penalty: u32,
+    pub attracts: u32,
+}
+
+/// Takes away points for feeding pests, and releases the pests they attract from random spawners.
+pub(super) fn pest_fed_sys(
+    mut cmd: Commands,
+    mut pest_fed_evr: EventReader<PestFedEvent>,
+    mut score_evw: EventWriter<ScoreEvent>,
+    spawners: Query<&Transform, With<BirdSpawner>>,
+    root: LevelRootEntity,
+) {
+    let mut rng = rand::rng();
+    let spawner_tfs: Vec<&Transform> = spawners.iter().collect();
+    for evt in pest_fed_evr.read() {
+        if evt.penalty > 0 {
+            score_evw.write(ScoreEvent { reason: ScoreReason::Pest, base: -(evt.penalty as i32), multiplier: 1. });
+        }
+        if spawner_tfs.is_empty() {
+            continue;
+        }
+        cmd.entity(*root).with_children(|builder| {
+            for _ in 0..evt.attracts {
+                let spawner_tf = spawner_tfs[rng.random_range(0..spawner_tfs.len())];
+                builder.spawn((AssetHandle::<BirdAsset>(evt.pest.clone()), *spawner_tf));
+            }
+        });
+    }
+}
+
+/// Scares off hungry pests within reach of a player's deterrent.
+pub(super) fn bird_deter_sys(
+    mut deter_evr: EventReader<DeterEvent>,
+    mut birds: Query<(Entity, &mut BirdBehaviour, &Transform, &AssetHandle<BirdAsset>)>,
+    mut state_evw: EventWriter<BirdStateEvent>,
+    assets: Res<Assets<BirdAsset>>,
+    time: Res<Time>,
+) {
+    for evt in deter_evr.read() {
+        for (entity, mut behaviour, tf, handle) in birds.iter_mut() {
+            let is_pest = assets
+                .get(&handle.0)
+                .is_some_and(|asset| matches!(asset.disposition, Disposition::Pest { .. }));
+            if !is_pest
+                || !behaviour.state().is_hungry()
+                || tf.translation.xy().distance(evt.position) > evt.radius
+            {
+                continue;
+            }
+            if let Some(state_evt) = behaviour.transition(entity, BirdState::Fleeing, time.elapsed_secs()) {
+                state_evw.write(state_evt);
+            }
+        }
+    }
+}
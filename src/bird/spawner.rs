@@ -1,16 +1,26 @@
-use super::asset::BirdAsset;
+use super::{asset::BirdAsset, flocking::FlockMember};
 use crate::{
-    level::{Level, LevelAsset, LevelEvent, LevelRootEntity},
+    level::{Level, LevelAsset, LevelBird, LevelEvent, LevelRootEntity, SpawnTelegraph, TelegraphStyle},
     util::AssetHandle, AppConfig
 };
 use bevy::{
-    color::palettes::css::{GREEN, PURPLE},
+    color::palettes::css::{GREEN, ORANGE_RED, PURPLE},
     platform::collections::HashMap,
     prelude::*,
 };
 use rand::Rng;
 use std::f32::consts::PI;
 
+/// Distance between birds in a flock when it is released.
+const FLOCK_SPAWN_SPACING: f32 = 60.;
+/// Width and height of the arrow shown by a [TelegraphStyle::Marker].
+const TELEGRAPH_MARKER_SIZE: f32 = 30.;
+/// Times per second a [TelegraphStyle::Marker] flashes.
+const TELEGRAPH_FLASH_RATE: f32 = 4.;
+const TELEGRAPH_SHADOW_SIZE: Vec2 = Vec2::new(40., 20.);
+/// Size of a [TelegraphStyle::Shadow] as the warning starts, as a fraction of its size once the bird arrives.
+const TELEGRAPH_SHADOW_START_SCALE: f32 = 0.2;
+
 #[derive(Component)]
 pub struct BirdSpawner {
     cooldown: f32,
@@ -29,8 +39,13 @@ pub(super) fn bird_spawn_sys(
     root: LevelRootEntity,
     mut gizmos: Gizmos,
     app_config: Res<AppConfig>,
+    mut next_flock: Local<u32>,
+    windows: Query<&Window>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
     let mut rng = rand::rng();
+    let window_height = windows.single().map_or(0., Window::height);
 
     if let Some(level_asset) = level_assets.get(&level.level_handle) {
         for (entity, spawner, spawner_tf) in spawners.iter() {
@@ -58,10 +73,30 @@ pub(super) fn bird_spawn_sys(
                     .unwrap_or(0);
                 let random_bird = &level_asset.birds[random_index];
 
-                cmd.entity(*root).with_child((
-                    AssetHandle::<BirdAsset>(asset_server.load(random_bird.asset.as_str())),
-                    spawner_tf.clone(),
-                ));
+                let flock = *next_flock;
+                *next_flock += 1;
+                match random_bird.telegraph.as_ref().or(level_asset.spawn_telegraph.as_ref()) {
+                    Some(telegraph) => {
+                        let marker_y = window_height * 0.5 - TELEGRAPH_MARKER_SIZE;
+                        spawn_telegraph(
+                            &mut cmd,
+                            *root,
+                            telegraph,
+                            PendingSpawn {
+                                bird: random_index,
+                                flock,
+                                spawner_tf: *spawner_tf,
+                                style: telegraph.style,
+                                timer: Timer::from_seconds(telegraph.lead_time, TimerMode::Once),
+                            },
+                            Vec3::new(spawner_tf.translation.x, marker_y, spawner_tf.translation.z),
+                            &asset_server,
+                            &mut meshes,
+                            &mut materials,
+                        );
+                    }
+                    None => spawn_flock(&mut cmd, *root, &asset_server, random_bird, spawner_tf, flock),
+                }
             }
 
             // Show position, direction, and status of spawners.
@@ -76,6 +111,173 @@ pub(super) fn bird_spawn_sys(
     }
 }
 
+/// Releases `bird` from a spawner, as a flock in a V behind the spawner if it has a `flock_size`.
+fn spawn_flock(
+    cmd: &mut Commands,
+    root: Entity,
+    asset_server: &AssetServer,
+    bird: &LevelBird,
+    spawner_tf: &Transform,
+    flock: u32,
+) {
+    let flock_size = bird.flock_size.unwrap_or(1);
+    cmd.entity(root).with_children(|builder| {
+        for i in 0..flock_size {
+            let row = i.div_ceil(2) as f32;
+            let side = if i % 2 == 0 { 1. } else { -1. };
+            let mut bird_tf = *spawner_tf;
+            bird_tf.translation += spawner_tf.rotation
+                * Vec3::new(side * row * FLOCK_SPAWN_SPACING, -row * FLOCK_SPAWN_SPACING, 0.);
+
+            let mut bird_cmds = builder.spawn((
+                AssetHandle::<BirdAsset>(asset_server.load(bird.asset.as_str())),
+                bird.tweaks.clone(),
+                bird_tf,
+            ));
+            if flock_size > 1 {
+                bird_cmds.insert(FlockMember { flock });
+            }
+        }
+    });
+}
+
+/// Bird waiting to be released from a spawner while a [SpawnTelegraph] warns it is coming.
+#[derive(Component)]
+pub(super) struct PendingSpawn {
+    /// Index of the bird within [LevelAsset::birds].
+    bird: usize,
+    flock: u32,
+    spawner_tf: Transform,
+    style: TelegraphStyle,
+    timer: Timer,
+}
+
+/// Shows the warning for `pending` at `translation`, on the edge of the screen above its spawner.
+fn spawn_telegraph(
+    cmd: &mut Commands,
+    root: Entity,
+    telegraph: &SpawnTelegraph,
+    pending: PendingSpawn,
+    translation: Vec3,
+    asset_server: &AssetServer,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+) {
+    let visual = match pending.style {
+        TelegraphStyle::Marker => Some((
+            Mesh2d(meshes.add(Triangle2d::new(
+                Vec2::new(-0.5, 0.5) * TELEGRAPH_MARKER_SIZE,
+                Vec2::new(0.5, 0.5) * TELEGRAPH_MARKER_SIZE,
+                Vec2::new(0., -0.5) * TELEGRAPH_MARKER_SIZE,
+            ))),
+            MeshMaterial2d(materials.add(Color::from(ORANGE_RED))),
+        )),
+        TelegraphStyle::Shadow => Some((
+            Mesh2d(meshes.add(Ellipse::new(TELEGRAPH_SHADOW_SIZE.x, TELEGRAPH_SHADOW_SIZE.y))),
+            MeshMaterial2d(materials.add(Color::srgba(0., 0., 0., 0.35))),
+        )),
+        TelegraphStyle::Hidden => None,
+    };
+    let scale = if pending.style == TelegraphStyle::Shadow { TELEGRAPH_SHADOW_START_SCALE } else { 1. };
+
+    cmd.entity(root).with_children(|builder| {
+        let mut pending_cmds = builder.spawn((
+            pending,
+            Transform::from_translation(translation).with_scale(Vec3::splat(scale)),
+            Visibility::default(),
+        ));
+        if let Some(visual) = visual {
+            pending_cmds.insert(visual);
+        }
+        // sound plays to the end even if the bird arrives first
+        if let Some(sound) = &telegraph.sound {
+            builder.spawn((AudioPlayer::new(asset_server.load(sound.clone())), PlaybackSettings::DESPAWN));
+        }
+    });
+}
+
+/// Animates spawn warnings, releasing each bird once its warning has run its course.
+pub(super) fn bird_telegraph_sys(
+    mut cmd: Commands,
+    mut pending_spawns: Query<(Entity, &mut PendingSpawn, &mut Transform, &mut Visibility)>,
+    level: Res<Level>,
+    level_assets: Res<Assets<LevelAsset>>,
+    asset_server: Res<AssetServer>,
+    root: LevelRootEntity,
+    time: Res<Time>,
+) {
+    for (entity, mut pending, mut tf, mut visibility) in pending_spawns.iter_mut() {
+        pending.timer.tick(time.delta());
+        if pending.timer.finished() {
+            cmd.entity(entity).despawn();
+            // the level may have been reloaded without the bird in the meantime
+            if let Some(bird) = level_assets.get(&level.level_handle).and_then(|l| l.birds.get(pending.bird)) {
+                spawn_flock(&mut cmd, *root, &asset_server, bird, &pending.spawner_tf, pending.flock);
+            }
+            continue;
+        }
+
+        match pending.style {
+            TelegraphStyle::Marker => {
+                let flash_on = ((pending.timer.elapsed_secs() * TELEGRAPH_FLASH_RATE * 2.) as u32).is_multiple_of(2);
+                visibility.set_if_neq(if flash_on { Visibility::Inherited } else { Visibility::Hidden });
+            }
+            TelegraphStyle::Shadow => {
+                let scale = TELEGRAPH_SHADOW_START_SCALE.lerp(1., pending.timer.fraction());
+                tf.scale = Vec3::splat(scale);
+            }
+            TelegraphStyle::Hidden => (),
+        }
+    }
+}
+
+/// Releases each of the level's waves once its time is reached, from a random spawner.
+pub(super) fn bird_wave_sys(
+    mut cmd: Commands,
+    mut level_evr: EventReader<LevelEvent>,
+    spawners: Query<&Transform, With<BirdSpawner>>,
+    level: Res<Level>,
+    level_assets: Res<Assets<LevelAsset>>,
+    asset_server: Res<AssetServer>,
+    root: LevelRootEntity,
+    time: Res<Time>,
+    mut level_started_at: Local<f32>,
+    mut next_wave: Local<usize>,
+) {
+    for ev in level_evr.read() {
+        if let LevelEvent::Loaded { .. } = ev {
+            *level_started_at = time.elapsed_secs();
+            *next_wave = 0;
+        }
+    }
+
+    let Some(level_asset) = level_assets.get(&level.level_handle) else {
+        return;
+    };
+    let Some(wave) = level_asset.waves.get(*next_wave) else {
+        return;
+    };
+    if time.elapsed_secs() - *level_started_at < wave.time || spawners.is_empty() {
+        return;
+    }
+    *next_wave += 1;
+
+    let mut rng = rand::rng();
+    let spawner_tfs: Vec<&Transform> = spawners.iter().collect();
+    cmd.entity(*root).with_children(|builder| {
+        for bird in &wave.birds {
+            for _ in 0..bird.count {
+                let spawner_tf = spawner_tfs[rng.random_range(0..spawner_tfs.len())];
+                builder.spawn((
+                    AssetHandle::<BirdAsset>(asset_server.load(bird.asset.as_str())),
+                    bird.tweaks.clone(),
+                    *spawner_tf,
+                ));
+            }
+        }
+    });
+}
+
 /// Spawns bird spawners.
 pub(super) fn setup_spawner_sys(
     mut cmd: Commands,
@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    Bird,
+    asset::BirdAsset,
+    behaviour::{BirdBehaviour, BirdState, BirdStateEvent},
+};
+use crate::{
+    status::{StatusEffects, StatusKind},
+    util::AssetHandle,
+    validate::{Validate, ValidationContext},
+};
+
+/// How a bird reacts to being ignored.
+///
+/// Hunger grows back over time, up to the bird's starting hunger,
+/// and anger builds while the bird goes unfed.
+/// Once anger is full the bird becomes [BirdState::Angry].
+#[derive(Debug, Deserialize, Clone)]
+pub struct BirdTemper {
+    /// Seconds for hunger to grow by one unit. Hunger does not grow if not given.
+    #[serde(default)]
+    pub hunger_interval: Option<f32>,
+    /// Anger gained each second while hungry, where 1 is full.
+    pub anger_rate: f32,
+    /// Anger lost each time the bird is fed.
+    #[serde(default = "default_calm_on_feed")]
+    pub calm_on_feed: f32,
+    /// Multiplier applied to [BirdAsset::drop_probability] once anger is full,
+    /// scaled down for birds that are only a little angry.
+    #[serde(default = "default_angry_drop_multiplier")]
+    pub angry_drop_multiplier: f32,
+    /// Whether angry birds aim their droppings at the closest player.
+    #[serde(default)]
+    pub aim_droppings: bool,
+}
+
+fn default_calm_on_feed() -> f32 {
+    0.5
+}
+
+fn default_angry_drop_multiplier() -> f32 {
+    3.
+}
+
+impl BirdTemper {
+    /// Multiplier for the chance of dropping something at `anger`.
+    pub fn drop_multiplier(&self, anger: f32) -> f32 {
+        1. + (self.angry_drop_multiplier - 1.) * anger
+    }
+}
+
+impl Validate for BirdTemper {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        if self.hunger_interval.is_some_and(|interval| interval <= 0.) {
+            ctx.error("hunger_interval", "hunger_interval must be positive");
+        }
+        if self.anger_rate < 0. {
+            ctx.error("anger_rate", "anger_rate must not be negative");
+        }
+        if self.calm_on_feed < 0. {
+            ctx.error("calm_on_feed", "calm_on_feed must not be negative");
+        }
+        if self.angry_drop_multiplier < 0. {
+            ctx.error("angry_drop_multiplier", "angry_drop_multiplier must not be negative");
+        }
+    }
+}
+
+/// Number of shades of anger the hunger bar shows between calm and fully angry.
+const ANGER_STEPS: f32 = 20.;
+
+/// Which of the [ANGER_STEPS] shades of anger `anger` falls in.
+fn anger_step(anger: f32) -> u32 {
+    (anger * ANGER_STEPS) as u32
+}
+
+/// Grows hunger and anger of birds that are waiting to be fed,
+/// making them [BirdState::Angry] once anger is full.
+///
+/// [StatusKind::Enraged] birds grow angrier faster, even without a temper.
+pub(super) fn bird_temper_sys(
+    mut birds: Query<(Entity, &mut Bird, &mut BirdBehaviour, &StatusEffects, &AssetHandle<BirdAsset>)>,
+    mut state_evw: EventWriter<BirdStateEvent>,
+    assets: Res<Assets<BirdAsset>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    for (entity, mut bird, mut behaviour, status, handle) in birds.iter_mut() {
+        let state = behaviour.state();
+        if !state.is_hungry() {
+            continue;
+        }
+        let temper = assets.get(&handle.0).and_then(|asset| asset.temper.as_ref());
+        let enraged = status.total(StatusKind::Enraged);
+        if temper.is_none() && enraged == 0. {
+            continue;
+        }
+
+        if let Some(interval) = temper.and_then(|temper| temper.hunger_interval) {
+            // only flag the bird as changed once its hunger actually grows
+            bird.bypass_change_detection().hunger_elapsed += delta;
+            if bird.hunger_elapsed >= interval {
+                bird.hunger_elapsed -= interval;
+                bird.hunger = (bird.hunger + 1).min(bird.initial_hunger);
+            }
+        }
+
+        let anger_rate = temper.map_or(0., |temper| temper.anger_rate) + enraged;
+        if bird.anger < 1. && anger_rate > 0. {
+            // only flag the bird as changed when its hunger bar shows a different shade of anger
+            let step = anger_step(bird.anger);
+            let anger = &mut bird.bypass_change_detection().anger;
+            *anger = (*anger + anger_rate * delta).min(1.);
+            if anger_step(*anger) != step {
+                bird.set_changed();
+            }
+        }
+        if bird.anger >= 1.
+            && state != BirdState::Angry
+            && let Some(evt) = behaviour.transition(entity, BirdState::Angry, time.elapsed_secs())
+        {
+            state_evw.write(evt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct ChangedTicks(u32);
+
+    fn count_changed_sys(changed: Query<(), Changed<Bird>>, mut ticks: ResMut<ChangedTicks>) {
+        if !changed.is_empty() {
+            ticks.0 += 1;
+        }
+    }
+
+    #[test]
+    fn growing_anger_only_changes_bird_when_its_shade_changes() {
+        let mut world = World::new();
+        let mut asset: BirdAsset =
+            ron::de::from_str(include_str!("../../assets/birds/canada_goose.ron")).unwrap();
+        asset.temper = Some(BirdTemper {
+            hunger_interval: None,
+            anger_rate: 0.1,
+            calm_on_feed: default_calm_on_feed(),
+            angry_drop_multiplier: default_angry_drop_multiplier(),
+            aim_droppings: false,
+        });
+        let mut assets = Assets::<BirdAsset>::default();
+        let handle = assets.add(asset);
+        world.insert_resource(assets);
+        world.init_resource::<Events<BirdStateEvent>>();
+        world.init_resource::<ChangedTicks>();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_millis(100));
+        world.insert_resource(time);
+        let bird = world
+            .spawn((
+                Bird {
+                    name: "goose".into(),
+                    velocity: 100.,
+                    hunger: 3,
+                    initial_hunger: 3,
+                    hunger_elapsed: 0.,
+                    anger: 0.,
+                    drop_probability: 0.,
+                    entered_screen: true,
+                },
+                BirdBehaviour::new(0.),
+                StatusEffects::default(),
+                AssetHandle(handle),
+            ))
+            .id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((bird_temper_sys, count_changed_sys).chain());
+        // the first run sees the bird as newly added
+        schedule.run(&mut world);
+        world.resource_mut::<ChangedTicks>().0 = 0;
+
+        // 0.01 anger a tick crosses a shade every 5 ticks
+        for _ in 0..10 {
+            schedule.run(&mut world);
+        }
+        assert!((world.get::<Bird>(bird).unwrap().anger - 0.11).abs() < 1e-4);
+        assert_eq!(world.resource::<ChangedTicks>().0, 2);
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{asset::io::file::FileAssetReader, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameState,
+    bird::{
+        asset::BirdAsset,
+        departure::{BirdArrived, BirdDeparted},
+    },
+};
+
+/// Directory within the asset root holding every bird shown in the Birdpedia.
+const BIRDS_DIR: &str = "birds";
+
+/// Keeps track of which birds the player has seen and fully fed, across sessions.
+///
+/// Counts are kept per species name, so a bird tweaked by a level counts as the same bird.
+pub struct BirdpediaPlugin {
+    /// File the counts are saved to, created the first time they are saved after a bird is seen.
+    pub save_path: PathBuf,
+}
+
+impl Plugin for BirdpediaPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Birdpedia::load(&self.save_path));
+        app.init_resource::<BirdpediaAssets>();
+        app.add_systems(Startup, load_birdpedia_assets_sys);
+        app.add_systems(FixedUpdate, record_birdpedia_sys.run_if(in_state(GameState::Game)));
+        // saving as birds come and go would write the file many times a second in busy waves
+        app.add_systems(OnExit(GameState::Game), save_birdpedia_sys);
+        app.add_systems(Last, save_birdpedia_sys.run_if(on_event::<AppExit>));
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BirdpediaEntry {
+    /// Number of times the species has flown onto the screen.
+    pub seen: u32,
+    /// Number of times the species has left with no hunger remaining.
+    pub fully_fed: u32,
+}
+
+#[derive(Debug, Resource)]
+pub struct Birdpedia {
+    entries: HashMap<String, BirdpediaEntry>,
+    save_path: PathBuf,
+    /// Whether the counts have changed since they were last saved.
+    unsaved: bool,
+}
+
+impl Birdpedia {
+    /// Reads the counts saved at `save_path`, starting afresh if there are none.
+    fn load(save_path: &Path) -> Self {
+        let entries = match fs::read_to_string(save_path) {
+            Ok(source) => ron::de::from_str(&source).unwrap_or_else(|err| {
+                warn!("Unable to read Birdpedia from {}: {}", save_path.display(), err);
+                default()
+            }),
+            Err(_) => default(),
+        };
+        Self { entries, save_path: save_path.to_path_buf(), unsaved: false }
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(&self.entries, default())
+            .map_err(|err| err.to_string())
+            .and_then(|source| fs::write(&self.save_path, source).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            warn!("Unable to save Birdpedia to {}: {}", self.save_path.display(), err);
+        }
+    }
+
+    /// Counts for `species`, if it has ever been seen.
+    pub fn entry(&self, species: &str) -> Option<&BirdpediaEntry> {
+        self.entries.get(species).filter(|entry| entry.seen > 0)
+    }
+}
+
+/// Every bird asset, loaded up front so the Birdpedia can list birds that have not been seen yet.
+#[derive(Debug, Default, Resource)]
+pub struct BirdpediaAssets(pub Vec<Handle<BirdAsset>>);
+
+fn load_birdpedia_assets_sys(mut assets: ResMut<BirdpediaAssets>, asset_server: Res<AssetServer>) {
+    let dir = FileAssetReader::get_base_path().join("assets").join(BIRDS_DIR);
+    let mut files = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+            .collect::<Vec<_>>(),
+        Err(err) => {
+            warn!("Unable to list birds in {}: {}", dir.display(), err);
+            return;
+        }
+    };
+    files.sort();
+
+    for file in files {
+        if let Some(name) = file.file_name() {
+            assets.0.push(asset_server.load(PathBuf::from(BIRDS_DIR).join(name)));
+        }
+    }
+}
+
+fn record_birdpedia_sys(
+    mut arrived_evr: EventReader<BirdArrived>,
+    mut departed_evr: EventReader<BirdDeparted>,
+    mut birdpedia: ResMut<Birdpedia>,
+) {
+    for arrived in arrived_evr.read() {
+        birdpedia.entries.entry(arrived.species.clone()).or_default().seen += 1;
+        birdpedia.unsaved = true;
+    }
+    for departed in departed_evr.read() {
+        if departed.fed_fully {
+            birdpedia.entries.entry(departed.species.clone()).or_default().fully_fed += 1;
+            birdpedia.unsaved = true;
+        }
+    }
+}
+
+/// Saves the counts when play stops or the game closes, if they have changed.
+fn save_birdpedia_sys(mut birdpedia: ResMut<Birdpedia>) {
+    if birdpedia.unsaved {
+        birdpedia.save();
+        birdpedia.unsaved = false;
+    }
+}
@@ -1,16 +1,28 @@
-use std::path::PathBuf;
+pub mod prefab;
+pub mod scatter;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
     color::palettes::css::{GREEN, ORANGE},
     image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
     math::Affine2, prelude::*,
 };
+use prefab::{LevelPrefabInstance, SceneryPrefabLibrary};
+use scatter::LevelScatter;
 use serde::Deserialize;
 
 use crate::{
     GameState,
-    physics::{Collider, ColliderContactEvent, ColliderIntersectionMode, ColliderStatic},
-    util::ron_asset_loader::RonAssetLoader,
+    bird::perch::Perch,
+    physics::{Collider, ColliderContactEvent, ColliderIntersectionMode, ColliderOffset, ColliderStatic, SelfDespawning},
+    projectile::ProjectileBlocker,
+    util::{DepthBand, DepthBands, YSort, ron_asset_loader::RonAssetLoader},
+    validate::{Validate, ValidationContext},
 };
 
 pub struct LevelPlugin {
@@ -28,7 +40,9 @@ impl Default for LevelPlugin {
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<LevelAsset>()
-            .init_asset_loader::<RonAssetLoader<LevelAsset>>()
+            .init_asset_loader::<LevelAssetLoader>()
+            .init_asset::<SceneryPrefabLibrary>()
+            .init_asset_loader::<RonAssetLoader<SceneryPrefabLibrary>>()
             .insert_resource(Level {
                 default_level_path: self.default_level.clone(),
                 ..default()
@@ -62,10 +76,61 @@ pub struct LevelAsset {
     pub spawn_cooldown: f32,
     pub spawner_qty: i32,
     pub spawner_z: f32,
+    /// Height above the ground that birds fly at, which their droppings fall from.
+    #[serde(default = "default_flight_altitude")]
+    pub flight_altitude: f32,
+    /// Warning shown by every spawner before a bird appears, unless the bird gives its own.
+    #[serde(default)]
+    pub spawn_telegraph: Option<SpawnTelegraph>,
     pub birds: Vec<LevelBird>,
+    /// Birds released at set times, such as bosses. Must be in order of time.
+    #[serde(default)]
+    pub waves: Vec<LevelWave>,
     pub players: Vec<LevelPlayer>,
     pub layers: Vec<LevelLayer>,
+    #[serde(default)]
     pub scenery: Vec<LevelScenery>,
+    /// Files containing a [SceneryPrefabLibrary] to take prefabs from.
+    #[serde(default)]
+    pub prefab_libraries: Vec<PathBuf>,
+    /// Named groups of scenery that can be placed multiple times with [LevelAsset::prefab_instances].
+    /// Prefabs defined here take priority over those with the same name in a library.
+    #[serde(default)]
+    pub prefabs: HashMap<String, Vec<LevelScenery>>,
+    #[serde(default)]
+    pub prefab_instances: Vec<LevelPrefabInstance>,
+    /// Regions to fill with randomly placed scenery.
+    #[serde(default)]
+    pub scatter: Vec<LevelScatter>,
+    /// Named ranges of Z values for entities that are depth sorted by their Y position.
+    #[serde(default)]
+    pub depth_bands: HashMap<String, DepthBand>,
+}
+
+impl LevelAsset {
+    /// Adds any prefabs that are not already defined by the level.
+    fn merge_prefabs(&mut self, prefabs: &HashMap<String, Vec<LevelScenery>>) {
+        for (name, scenery) in prefabs {
+            self.prefabs.entry(name.clone()).or_insert_with(|| scenery.clone());
+        }
+    }
+
+    /// All scenery in the level, including that placed by prefab instances,
+    /// along with the translation each should be spawned at.
+    fn all_scenery(&self) -> impl Iterator<Item = (&LevelScenery, Vec3)> {
+        let placed = self.scenery.iter().map(|scenery| (scenery, scenery.translation));
+        let instanced = self.prefab_instances.iter().flat_map(|instance| {
+            let prefab = self.prefabs.get(&instance.prefab);
+            if prefab.is_none() {
+                warn!("Level uses unknown prefab {}", instance.prefab);
+            }
+            prefab
+                .into_iter()
+                .flatten()
+                .map(|scenery| (scenery, instance.translation + scenery.translation))
+        });
+        placed.chain(instanced)
+    }
 }
 
 /// Bird used in the level.
@@ -73,12 +138,110 @@ pub struct LevelAsset {
 pub struct LevelBird {
     pub asset: String,
     pub spawn_probability: f32,
+    /// Number of birds released together each time this bird is picked.
+    #[serde(default)]
+    pub flock_size: Option<u32>,
+    #[serde(default)]
+    pub tweaks: BirdTweaks,
+    /// Warning before this bird appears, in place of [LevelAsset::spawn_telegraph].
+    #[serde(default)]
+    pub telegraph: Option<SpawnTelegraph>,
+}
+
+/// Warning given a little while before a bird appears from a spawner,
+/// so fast birds do not take the player by surprise.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SpawnTelegraph {
+    /// Seconds between the warning and the bird appearing.
+    pub lead_time: f32,
+    #[serde(default)]
+    pub style: TelegraphStyle,
+    /// Sound played as the warning starts.
+    #[serde(default)]
+    pub sound: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum TelegraphStyle {
+    /// Flashing arrow on the edge of the screen above the spawner.
+    #[default]
+    Marker,
+    /// Shadow that grows as the bird gets closer.
+    Shadow,
+    /// Only the sound, if any.
+    Hidden,
+}
+
+impl Validate for SpawnTelegraph {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        if self.lead_time <= 0. {
+            ctx.error("lead_time", "lead_time must be positive");
+        }
+        if let Some(sound) = &self.sound {
+            ctx.check_asset_exists(sound);
+        }
+    }
+}
+
+/// Changes to a bird for just this level, such as a faster or hungrier magpie,
+/// saving a new bird asset for each small variation.
+#[derive(Debug, Deserialize, Clone, Component)]
+#[serde(default)]
+pub struct BirdTweaks {
+    /// Multiplies the bird's velocity.
+    pub velocity_multiplier: f32,
+    /// Added to the bird's starting hunger, which stays at least 1.
+    pub hunger_offset: i8,
+    /// Colour multiplied with the bird's sprite.
+    pub tint: Option<LinearRgba>,
+}
+
+impl Default for BirdTweaks {
+    fn default() -> Self {
+        Self { velocity_multiplier: 1., hunger_offset: 0, tint: None }
+    }
+}
+
+impl Validate for BirdTweaks {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        if self.velocity_multiplier <= 0. {
+            ctx.error("velocity_multiplier", "velocity_multiplier must be positive");
+        }
+    }
+}
+
+/// Birds released at a set time after the level starts, rather than at random.
+#[derive(Debug, Deserialize)]
+pub struct LevelWave {
+    /// Seconds after the level has loaded.
+    pub time: f32,
+    pub birds: Vec<LevelWaveBird>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LevelWaveBird {
+    pub asset: String,
+    #[serde(default = "default_wave_bird_count")]
+    pub count: u32,
+    #[serde(default)]
+    pub tweaks: BirdTweaks,
+}
+
+fn default_flight_altitude() -> f32 {
+    150.
+}
+
+fn default_wave_bird_count() -> u32 {
+    1
 }
 
 #[derive(Debug, Deserialize)]
 pub struct LevelPlayer {
     pub asset: PathBuf,
     pub initial_position: Vec3,
+    /// Depth band to sort the player within, see [YSort].
+    #[serde(default)]
+    pub y_sort: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -97,13 +260,214 @@ pub enum LayerDisplayMode {
     Fit,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct LevelScenery {
     image: PathBuf,
     size: Vec2,
     translation: Vec3,
     collision: bool,
+    /// Size of a [SceneryColliderShape::Rectangle] collider.
     collider_size: Vec2,
+    #[serde(default)]
+    collider_shape: SceneryColliderShape,
+    /// Position of the collider relative to the centre of the sprite.
+    #[serde(default)]
+    collider_offset: Vec2,
+    /// Rotation of the collider in degrees.
+    #[serde(default)]
+    collider_rotation: f32,
+    #[serde(default)]
+    collider_flags: SceneryColliderFlags,
+    /// Depth band to sort within using the bottom of the sprite, see [YSort].
+    /// When given the Z translation is ignored.
+    #[serde(default)]
+    y_sort: Option<String>,
+    /// Points birds can land on, relative to the centre of the sprite, see [Perch].
+    #[serde(default)]
+    perches: Vec<Vec2>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub enum SceneryColliderShape {
+    #[default]
+    Rectangle,
+    Circle {
+        radius: f32,
+    },
+}
+
+/// What a scenery collider interacts with.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SceneryColliderFlags {
+    /// Stops players moving through the collider.
+    blocks_players: bool,
+    /// Stops projectiles that hit the collider.
+    blocks_projectiles: bool,
+    /// Only reports contacts, blocking nothing regardless of the other flags.
+    sensor: bool,
+}
+
+impl Default for SceneryColliderFlags {
+    fn default() -> Self {
+        Self { blocks_players: true, blocks_projectiles: false, sensor: false }
+    }
+}
+
+impl Validate for LevelAsset {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        ctx.check_probability(self.spawn_probability, "spawn_probability");
+        if self.spawn_cooldown < 0. {
+            ctx.error("spawn_cooldown", "spawn_cooldown must not be negative");
+        }
+        // Spawners are spread evenly across the window, see `setup_spawner_sys`.
+        if self.spawner_qty <= 1 {
+            ctx.error("spawner_qty", format!("spawner_qty must be greater than 1, found {}", self.spawner_qty));
+        }
+
+        if self.flight_altitude < 0. {
+            ctx.error("flight_altitude", "flight_altitude must not be negative");
+        }
+        if let Some(telegraph) = &self.spawn_telegraph {
+            telegraph.validate(ctx);
+        }
+        if self.birds.is_empty() {
+            ctx.error("birds", "level must have at least one bird");
+        }
+        for bird in &self.birds {
+            ctx.check_weight(bird.spawn_probability, &bird.asset);
+            if bird.flock_size == Some(0) {
+                ctx.error("flock_size", "flock_size must be at least 1");
+            }
+            bird.tweaks.validate(ctx);
+            if let Some(telegraph) = &bird.telegraph {
+                telegraph.validate(ctx);
+            }
+            ctx.check_ron_asset(Path::new(&bird.asset));
+        }
+
+        for (i, wave) in self.waves.iter().enumerate() {
+            if wave.time < 0. {
+                ctx.error("time", "wave time must not be negative");
+            }
+            if i > 0 && wave.time < self.waves[i - 1].time {
+                ctx.error("time", "waves must be in order of time");
+            }
+            if wave.birds.is_empty() {
+                ctx.error("waves", "wave must have at least one bird");
+            }
+            for bird in &wave.birds {
+                bird.tweaks.validate(ctx);
+                ctx.check_ron_asset(Path::new(&bird.asset));
+            }
+        }
+
+        if self.players.is_empty() {
+            ctx.error("players", "level must have at least one player");
+        }
+        for player in &self.players {
+            ctx.check_ron_asset(&player.asset);
+        }
+
+        for (name, band) in &self.depth_bands {
+            if band.y.x >= band.y.y {
+                ctx.error(&format!("\"{}\"", name), format!("depth band `{}` must have a y range from low to high", name));
+            }
+        }
+        let y_sort_bands = self
+            .players
+            .iter()
+            .map(|player| &player.y_sort)
+            .chain(self.scenery.iter().chain(self.prefabs.values().flatten()).map(|s| &s.y_sort))
+            .chain(self.scatter.iter().map(|scatter| &scatter.y_sort));
+        for band in y_sort_bands.flatten() {
+            if !self.depth_bands.contains_key(band) {
+                ctx.error(&format!("\"{}\"", band), format!("unknown depth band `{}`", band));
+            }
+        }
+
+        for layer in &self.layers {
+            ctx.check_asset_exists(&layer.image);
+        }
+        for scenery in self.scenery.iter().chain(self.prefabs.values().flatten()) {
+            scenery.validate(ctx);
+        }
+
+        for scatter in &self.scatter {
+            scatter.validate(ctx);
+        }
+
+        let mut libraries = vec![];
+        for path in &self.prefab_libraries {
+            ctx.check_ron_asset(path);
+            libraries.extend(ctx.read_ron_asset::<SceneryPrefabLibrary>(path));
+        }
+        for instance in &self.prefab_instances {
+            let known = self.prefabs.contains_key(&instance.prefab)
+                || libraries.iter().any(|library| library.prefabs.contains_key(&instance.prefab));
+            if !known {
+                ctx.error(&format!("\"{}\"", instance.prefab), format!("unknown prefab `{}`", instance.prefab));
+            }
+        }
+    }
+}
+
+impl Validate for LevelScenery {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        ctx.check_asset_exists(&self.image);
+        if !self.collision {
+            return;
+        }
+        match self.collider_shape {
+            SceneryColliderShape::Rectangle if self.collider_size.x <= 0. || self.collider_size.y <= 0. => {
+                ctx.error("collider_size", "collider_size must be positive when collision is enabled");
+            }
+            SceneryColliderShape::Circle { radius } if radius <= 0. => {
+                ctx.error("radius", "collider radius must be positive");
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Loads a [LevelAsset] along with each [SceneryPrefabLibrary] it depends on.
+///
+/// Library prefabs are merged into [LevelAsset::prefabs],
+/// so the rest of the game does not need to know where a prefab came from.
+#[derive(Default)]
+struct LevelAssetLoader;
+
+impl AssetLoader for LevelAssetLoader {
+    type Asset = LevelAsset;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut level = ron::de::from_bytes::<LevelAsset>(&bytes)?;
+
+        // Loading immediately also registers the library as a dependency,
+        // so editing it will reload the level.
+        for path in level.prefab_libraries.clone() {
+            let library = load_context
+                .loader()
+                .immediate()
+                .load::<SceneryPrefabLibrary>(path)
+                .await?;
+            level.merge_prefabs(&library.get().prefabs);
+        }
+        Ok(level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
 }
 
 /// Wait for current level asset to load then setup game and transition to [GameState::Game] when ready.
@@ -123,8 +487,13 @@ fn setup_level_plugin_sys(mut cmd: Commands) {
     ));
 }
 
+/// Marks scenery placed by the level, as opposed to the colliders bounding the play area.
+#[derive(Component)]
+pub struct Scenery;
+
+/// Despawns anything that touches it, used to clear up entities that have left the play area.
 #[derive(Component)]
-struct Despawner;
+pub struct Despawner;
 
 /// Marks the loaded levels root [Entity].
 ///
@@ -164,6 +533,9 @@ fn on_level_load_sys(
 
                 // Reset level
                 level.score = 0;
+                root_cmds
+                    .commands()
+                    .insert_resource(DepthBands(level_asset.depth_bands.clone().into_iter().collect()));
 
                 let window = windows.single().expect("Application should have a window");
                 let width = window.width();
@@ -236,10 +608,11 @@ fn on_level_load_sys(
                 }
 
                 // Spawn scenery
-                for scenery in &level_asset.scenery {
+                for (scenery, translation) in level_asset.all_scenery() {
                     root_cmds.with_children(|builder| {
                         let mut scenery_cmds = builder.spawn((
-                            Transform::from_translation(scenery.translation),
+                            Scenery,
+                            Transform::from_translation(translation),
                             Sprite {
                                 image: asset_server.load(scenery.image.clone()),
                                 custom_size: Some(scenery.size),
@@ -248,15 +621,65 @@ fn on_level_load_sys(
                         ));
 
                         if scenery.collision {
+                            let flags = &scenery.collider_flags;
                             scenery_cmds.insert((
-                                Collider::Rectangle(Rectangle::from_size(scenery.collider_size)),
-                                ColliderIntersectionMode::None,
+                                match scenery.collider_shape {
+                                    SceneryColliderShape::Rectangle => {
+                                        Collider::Rectangle(Rectangle::from_size(scenery.collider_size))
+                                    }
+                                    SceneryColliderShape::Circle { radius } => {
+                                        Collider::Circle(Circle::new(radius))
+                                    }
+                                },
+                                ColliderOffset {
+                                    translation: scenery.collider_offset,
+                                    rotation: scenery.collider_rotation.to_radians(),
+                                },
+                                // players can only be blocked by other colliders that allow no intersection
+                                if flags.blocks_players && !flags.sensor {
+                                    ColliderIntersectionMode::None
+                                } else {
+                                    ColliderIntersectionMode::AllowAll
+                                },
                                 ColliderStatic,
                             ));
+
+                            if flags.blocks_projectiles && !flags.sensor {
+                                scenery_cmds.insert(ProjectileBlocker);
+                            }
+                        }
+
+                        if let Some(band) = &scenery.y_sort {
+                            scenery_cmds.insert(YSort::new(band.clone()).with_offset(-scenery.size.y * 0.5));
+                        }
+
+                        for perch in &scenery.perches {
+                            builder.spawn((Perch, Transform::from_translation(translation + perch.extend(0.))));
                         }
                     });
                 }
 
+                // Spawn scattered scenery
+                for scatter in &level_asset.scatter {
+                    for scenery in scatter.generate() {
+                        root_cmds.with_children(|builder| {
+                            let mut scenery_cmds = builder.spawn((
+                                Scenery,
+                                Transform::from_translation(scenery.translation),
+                                Sprite {
+                                    image: asset_server.load(scenery.image.clone()),
+                                    custom_size: Some(scenery.size),
+                                    ..default()
+                                },
+                            ));
+
+                            if let Some(band) = &scatter.y_sort {
+                                scenery_cmds.insert(YSort::new(band.clone()).with_offset(-scenery.size.y * 0.5));
+                            }
+                        });
+                    }
+                }
+
                 level_evtw.send(LevelEvent::Loaded { id: *id });
             }
             AssetEvent::Unused { id } => {
@@ -299,13 +722,14 @@ fn despawn_entities(
     mut cmd: Commands,
     mut collision_evts: EventReader<ColliderContactEvent>,
     despawners: Query<(Entity, &Despawner)>,
+    self_despawning: Query<(), With<SelfDespawning>>,
 ) {
     for event in collision_evts.read() {
         if let Some(_) = event.either_entity(&despawners) {
-            if !despawners.contains(event.a) {
+            if !despawners.contains(event.a) && !self_despawning.contains(event.a) {
                 cmd.entity(event.a).try_despawn();
             }
-            if !despawners.contains(event.b) {
+            if !despawners.contains(event.b) && !self_despawning.contains(event.b) {
                 cmd.entity(event.b).try_despawn();
             }
         };
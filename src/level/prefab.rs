@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::LevelScenery;
+use crate::validate::{Validate, ValidationContext};
+
+/// Named groups of [LevelScenery] that can be shared between levels.
+///
+/// Levels pull these in by listing the file in [super::LevelAsset::prefab_libraries].
+#[derive(Asset, TypePath, Debug, Deserialize, Default)]
+pub struct SceneryPrefabLibrary {
+    pub prefabs: HashMap<String, Vec<LevelScenery>>,
+}
+
+impl Validate for SceneryPrefabLibrary {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        for scenery in self.prefabs.values().flatten() {
+            scenery.validate(ctx);
+        }
+    }
+}
+
+/// Places a copy of a prefab within the level.
+///
+/// The translation of each [LevelScenery] in the prefab is relative to `translation`.
+#[derive(Debug, Deserialize)]
+pub struct LevelPrefabInstance {
+    pub prefab: String,
+    pub translation: Vec3,
+}
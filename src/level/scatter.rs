@@ -0,0 +1,312 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use rand::{
+    Rng, SeedableRng,
+    distr::{Distribution, weighted::WeightedIndex},
+    rngs::StdRng,
+};
+use serde::Deserialize;
+
+use crate::validate::{Validate, ValidationContext};
+
+/// Area, in square pixels, that [LevelScatter::density] is measured against.
+const DENSITY_AREA: f32 = 100. * 100.;
+
+/// Number of attempts made to place each item before giving up,
+/// used to stop dense regions with a large [LevelScatter::min_spacing] from looping forever.
+const PLACEMENT_ATTEMPTS: usize = 30;
+
+/// Scenery placed at random within a region of the level.
+///
+/// Positions are generated from `seed` so the layout is the same each time the level is loaded.
+#[derive(Debug, Deserialize)]
+pub struct LevelScatter {
+    region: ScatterRegion,
+    images: Vec<ScatterImage>,
+    /// Average number of items per 100x100 pixel area.
+    density: f32,
+    /// Fraction each item's size may randomly vary by, e.g. `0.2` for +/- 20%.
+    #[serde(default)]
+    size_jitter: f32,
+    /// Closest that the centres of any two items may be.
+    #[serde(default)]
+    min_spacing: f32,
+    seed: u64,
+    z: f32,
+    /// Depth band to sort each item within using the bottom of its sprite, see [crate::util::YSort].
+    /// When given `z` is ignored.
+    #[serde(default)]
+    pub y_sort: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub enum ScatterRegion {
+    Rectangle { center: Vec2, size: Vec2 },
+    /// Points of a simple polygon, in order.
+    Polygon { points: Vec<Vec2> },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScatterImage {
+    image: PathBuf,
+    size: Vec2,
+    probability: f32,
+}
+
+/// An item generated by [LevelScatter::generate].
+pub struct ScatteredScenery<'a> {
+    pub image: &'a PathBuf,
+    pub size: Vec2,
+    pub translation: Vec3,
+}
+
+impl ScatterRegion {
+    fn bounds(&self) -> Rect {
+        match self {
+            ScatterRegion::Rectangle { center, size } => Rect::from_center_size(*center, *size),
+            ScatterRegion::Polygon { points } => points
+                .iter()
+                .fold(Rect::EMPTY, |rect, point| rect.union_point(*point)),
+        }
+    }
+
+    fn area(&self) -> f32 {
+        match self {
+            ScatterRegion::Rectangle { size, .. } => size.x * size.y,
+            // shoelace formula
+            ScatterRegion::Polygon { points } => {
+                let twice_area: f32 = points
+                    .iter()
+                    .zip(points.iter().cycle().skip(1))
+                    .map(|(a, b)| a.perp_dot(*b))
+                    .sum();
+                twice_area.abs() * 0.5
+            }
+        }
+    }
+
+    fn contains(&self, point: Vec2) -> bool {
+        match self {
+            ScatterRegion::Rectangle { center, size } => {
+                Rect::from_center_size(*center, *size).contains(point)
+            }
+            // even-odd ray casting
+            ScatterRegion::Polygon { points } => {
+                let mut inside = false;
+                for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+                    if (a.y > point.y) != (b.y > point.y)
+                        && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+                    {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
+        }
+    }
+}
+
+impl LevelScatter {
+    /// Picks positions, images and sizes for each item in the region.
+    pub fn generate(&self) -> Vec<ScatteredScenery<'_>> {
+        let Ok(image_dist) = WeightedIndex::new(self.images.iter().map(|i| i.probability)) else {
+            warn!("Scatter region has no images with a positive probability");
+            return vec![];
+        };
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let bounds = self.region.bounds();
+        let count = (self.region.area() * self.density / DENSITY_AREA).round() as usize;
+        let min_spacing_squared = self.min_spacing * self.min_spacing;
+
+        let mut positions: Vec<Vec2> = Vec::with_capacity(count);
+        let mut scattered = Vec::with_capacity(count);
+        for _ in 0..count * PLACEMENT_ATTEMPTS {
+            if positions.len() == count {
+                break;
+            }
+
+            let point = Vec2::new(
+                rng.random_range(bounds.min.x..=bounds.max.x),
+                rng.random_range(bounds.min.y..=bounds.max.y),
+            );
+            if !self.region.contains(point)
+                || positions.iter().any(|p| p.distance_squared(point) < min_spacing_squared)
+            {
+                continue;
+            }
+
+            let image = &self.images[image_dist.sample(&mut rng)];
+            let scale = if self.size_jitter > 0. {
+                1. + rng.random_range(-self.size_jitter..=self.size_jitter)
+            } else {
+                1.
+            };
+
+            positions.push(point);
+            scattered.push(ScatteredScenery {
+                image: &image.image,
+                size: image.size * scale,
+                translation: point.extend(self.z),
+            });
+        }
+
+        if scattered.len() < count {
+            debug!("Only placed {} of {} scattered scenery items", scattered.len(), count);
+        }
+        scattered
+    }
+}
+
+impl Validate for LevelScatter {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        match &self.region {
+            ScatterRegion::Rectangle { size, .. } if size.x <= 0. || size.y <= 0. => {
+                ctx.error("Rectangle", "scatter rectangle size must be positive");
+            }
+            ScatterRegion::Polygon { points } if points.len() < 3 => {
+                ctx.error("Polygon", "scatter polygon must have at least 3 points");
+            }
+            _ => (),
+        }
+        if self.images.is_empty() {
+            ctx.error("images", "scatter region must have at least one image");
+        }
+        for image in &self.images {
+            ctx.check_asset_exists(&image.image);
+            ctx.check_weight(image.probability, &image.image.to_string_lossy());
+        }
+        if self.density < 0. {
+            ctx.error("density", "density must not be negative");
+        }
+        if !(0. ..1.).contains(&self.size_jitter) {
+            ctx.error("size_jitter", "size_jitter must be at least 0 and less than 1");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> ScatterRegion {
+        ScatterRegion::Polygon {
+            points: vec![
+                Vec2::new(0., 0.),
+                Vec2::new(100., 0.),
+                Vec2::new(100., 100.),
+                Vec2::new(0., 100.),
+            ],
+        }
+    }
+
+    fn scatter(seed: u64) -> LevelScatter {
+        LevelScatter {
+            region: ScatterRegion::Rectangle {
+                center: Vec2::ZERO,
+                size: Vec2::new(400., 200.),
+            },
+            images: vec![
+                ScatterImage {
+                    image: PathBuf::from("leaf.png"),
+                    size: Vec2::splat(50.),
+                    probability: 3.,
+                },
+                ScatterImage {
+                    image: PathBuf::from("mushroom.png"),
+                    size: Vec2::splat(64.),
+                    probability: 1.,
+                },
+            ],
+            density: 1.,
+            size_jitter: 0.2,
+            min_spacing: 20.,
+            seed,
+            z: 15.,
+            y_sort: None,
+        }
+    }
+
+    #[test]
+    fn area_of_rectangles_and_polygons() {
+        let rectangle = ScatterRegion::Rectangle {
+            center: Vec2::new(50., -20.),
+            size: Vec2::new(40., 30.),
+        };
+        assert_eq!(rectangle.area(), 1200.);
+        assert_eq!(square().area(), 10000.);
+
+        // the same triangle wound either way
+        let mut points = vec![Vec2::new(0., 0.), Vec2::new(100., 0.), Vec2::new(0., 50.)];
+        assert_eq!(
+            ScatterRegion::Polygon {
+                points: points.clone()
+            }
+            .area(),
+            2500.
+        );
+        points.reverse();
+        assert_eq!(ScatterRegion::Polygon { points }.area(), 2500.);
+    }
+
+    #[test]
+    fn contains_points_inside_only() {
+        let rectangle = ScatterRegion::Rectangle {
+            center: Vec2::ZERO,
+            size: Vec2::new(40., 20.),
+        };
+        assert!(rectangle.contains(Vec2::new(19., 9.)));
+        assert!(!rectangle.contains(Vec2::new(21., 0.)));
+
+        assert!(square().contains(Vec2::new(50., 50.)));
+        assert!(!square().contains(Vec2::new(150., 50.)));
+        assert!(!square().contains(Vec2::new(50., -1.)));
+
+        // an L shape, whose bounds include the missing corner
+        let l_shape = ScatterRegion::Polygon {
+            points: vec![
+                Vec2::new(0., 0.),
+                Vec2::new(100., 0.),
+                Vec2::new(100., 50.),
+                Vec2::new(50., 50.),
+                Vec2::new(50., 100.),
+                Vec2::new(0., 100.),
+            ],
+        };
+        assert!(l_shape.contains(Vec2::new(25., 75.)));
+        assert!(l_shape.contains(Vec2::new(75., 25.)));
+        assert!(!l_shape.contains(Vec2::new(75., 75.)));
+    }
+
+    #[test]
+    fn generate_is_the_same_for_the_same_seed() {
+        let layout = |seed| {
+            scatter(seed)
+                .generate()
+                .iter()
+                .map(|item| (item.image.clone(), item.size, item.translation))
+                .collect::<Vec<_>>()
+        };
+
+        let first = layout(7);
+        assert!(!first.is_empty());
+        assert_eq!(first, layout(7));
+        assert_ne!(first, layout(8));
+    }
+
+    #[test]
+    fn generate_places_items_within_the_region_and_apart() {
+        let scatter = scatter(1);
+        let items = scatter.generate();
+        // 400x200 at one item per 100x100
+        assert_eq!(items.len(), 8);
+        for (i, item) in items.iter().enumerate() {
+            assert!(scatter.region.contains(item.translation.xy()));
+            assert_eq!(item.translation.z, 15.);
+            for other in &items[i + 1..] {
+                assert!(item.translation.distance(other.translation) >= scatter.min_spacing);
+            }
+        }
+    }
+}
@@ -1,24 +1,31 @@
 #![feature(let_chains)]
 
 mod bird;
+mod birdpedia;
 mod level;
 mod physics;
 mod player;
 mod projectile;
+mod score;
+mod status;
 mod ui;
 mod util;
+mod validate;
 
 use std::{path::PathBuf, sync::LazyLock};
 
 use bevy::{prelude::*, window::WindowResolution};
 use bird::BirdPlugin;
-use clap::{Parser, ValueEnum};
+use birdpedia::BirdpediaPlugin;
+use clap::{Parser, Subcommand, ValueEnum};
 use level::LevelPlugin;
 use physics::PhysicsPlugin;
 use player::PlayerPlugin;
 use projectile::{ProjectileLauncher, ProjectilePlugin};
+use score::ScorePlugin;
+use status::StatusPlugin;
 use ui::UiPlugin;
-use util::TransformInterpolationPlugin;
+use util::{TransformInterpolationPlugin, YSortPlugin};
 
 static GAME_BACKGROUND_COLOR: LazyLock<Color> = LazyLock::new(|| Color::srgb_u8(56, 47, 30));
 
@@ -42,10 +49,35 @@ struct Args {
 
     #[arg(long)]
     debug_render: Option<bool>,
+
+    /// File the Birdpedia is saved to.
+    #[arg(long, default_value = "birdpedia.ron")]
+    birdpedia: PathBuf,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check level, bird, dropping and player files for errors without opening a window.
+    Validate {
+        /// Files, or directories containing `.ron` files, to validate.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Directory that paths referenced by assets are relative to.
+        #[arg(long, default_value = "assets")]
+        asset_root: PathBuf,
+    },
 }
 
 fn main() {
     let args = Args::parse();
+    if let Some(Command::Validate { paths, asset_root }) = &args.command {
+        std::process::exit(validate::run(paths, asset_root));
+    }
+
     let mut app = App::new();
     app.add_plugins((
         DefaultPlugins.set(ImagePlugin::default_nearest()).set(WindowPlugin {
@@ -65,10 +97,14 @@ fn main() {
         }),
         PhysicsPlugin { debug_render: args.debug_render.unwrap_or_default() },
         ProjectilePlugin,
+        ScorePlugin,
         BirdPlugin,
+        BirdpediaPlugin { save_path: args.birdpedia.clone() },
         UiPlugin,
         TransformInterpolationPlugin,
+        YSortPlugin,
         PlayerPlugin,
+        StatusPlugin,
         match args.level {
             Some(level) => LevelPlugin { default_level: PathBuf::from(level) },
             None => LevelPlugin::default(),
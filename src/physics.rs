@@ -34,19 +34,25 @@ impl Plugin for PhysicsPlugin {
 #[derive(Component)]
 pub struct Velocity(pub f32);
 
+/// Marks entities that clear themselves up once they leave the play area,
+/// so are not despawned on moving off the bottom of the screen or touching a
+/// [crate::level::Despawner].
+#[derive(Component)]
+pub struct SelfDespawning;
+
 fn velocity_move_sys(
     mut cmd: Commands,
-    mut entities: Query<(Entity, &mut Transform, &Velocity)>,
+    mut entities: Query<(Entity, &mut Transform, &Velocity, Has<SelfDespawning>)>,
     windows: Query<&Window>,
     time: Res<Time>,
 ) {
     let height = windows.single().expect("Application should have a window").height();
-    for (entity, mut tf, velocity) in entities.iter_mut() {
+    for (entity, mut tf, velocity, self_despawning) in entities.iter_mut() {
         let forward = tf.rotation * Vec3::Y;
         let distance = velocity.0 * time.delta_secs();
         tf.translation += forward * distance;
 
-        if tf.translation.y < -height / 2. - 50. {
+        if !self_despawning && tf.translation.y < -height / 2. - 50. {
             cmd.entity(entity).despawn();
         }
     }
@@ -56,6 +62,34 @@ fn velocity_move_sys(
 #[require(ColliderAabb, ColliderIntersectionMode)]
 pub enum Collider {
     Rectangle(Rectangle),
+    Circle(Circle),
+}
+
+impl Collider {
+    /// Axis aligned box enclosing the collider of an entity at `tf`.
+    pub fn aabb(&self, tf: &Transform, offset: Option<&ColliderOffset>) -> Aabb2d {
+        let translation = tf.translation.xy();
+        let rotation = tf.rotation.to_euler(EulerRot::YXZ).2;
+        let mut isometry = Isometry2d::new(translation, Rot2::radians(rotation));
+        if let Some(offset) = offset {
+            isometry = isometry * Isometry2d::new(offset.translation, Rot2::radians(offset.rotation));
+        }
+        match self {
+            Collider::Rectangle(rectangle) => rectangle.aabb_2d(isometry),
+            Collider::Circle(circle) => circle.aabb_2d(isometry),
+        }
+    }
+}
+
+/// Positions a [Collider] relative to the [Transform] of its entity.
+///
+/// Contacts are still tested using axis aligned bounding boxes,
+/// so a rotated collider will cover the box that encloses it.
+#[derive(Debug, Default, Component)]
+pub struct ColliderOffset {
+    pub translation: Vec2,
+    /// Rotation in radians.
+    pub rotation: f32,
 }
 
 #[derive(Debug, Component)]
@@ -74,15 +108,13 @@ pub enum ColliderIntersectionMode {
 }
 
 fn update_collider_aabb_sys(
-    mut colliders: Query<(&mut ColliderAabb, &Collider, &Transform), Changed<Transform>>,
+    mut colliders: Query<
+        (&mut ColliderAabb, &Collider, &Transform, Option<&ColliderOffset>),
+        Or<(Changed<Transform>, Changed<ColliderOffset>)>,
+    >,
 ) {
-    for (mut aabb, collider, tf) in colliders.iter_mut() {
-        let translation = tf.translation.xy();
-        let rotation = tf.rotation.to_euler(EulerRot::YXZ).2;
-        let isometry = Isometry2d::new(translation, Rot2::radians(rotation));
-        aabb.0 = match collider {
-            Collider::Rectangle(rectangle) => Some(rectangle.aabb_2d(isometry)),
-        }
+    for (mut aabb, collider, tf, offset) in colliders.iter_mut() {
+        aabb.0 = Some(collider.aabb(tf, offset));
     }
 }
 
@@ -7,23 +7,28 @@ use crate::{
     GameState,
     level::{LevelAsset, LevelEvent, LevelRootEntity},
     physics::{Collider, ColliderIntersectionMode},
-    projectile::ProjectileLauncher,
-    util::{AssetHandle, AssetManagerPlugin, EntityAssetReadyEvent},
+    projectile::{Food, ProjectileLauncher},
+    status::StatusEffects,
+    util::{AssetHandle, AssetManagerPlugin, EntityAssetReadyEvent, YSort},
+    validate::{Validate, ValidationContext},
 };
 
 const PLAYER_SPRINT_MULTIPLIER: f32 = 3.;
+const PLAYER_SPRITE_SIZE: f32 = 75.;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(AssetManagerPlugin::<PlayerAsset>::default());
+        app.add_event::<DeterEvent>();
         app.add_systems(
             Update,
             (
                 setup_player_sys,
                 on_player_asset_ready_sys,
                 player_move_sys.run_if(in_state(GameState::Game)),
+                player_deter_sys.run_if(in_state(GameState::Game)),
             ),
         );
     }
@@ -32,7 +37,19 @@ impl Plugin for PlayerPlugin {
 #[derive(Debug, Component)]
 pub struct Player {
     pub health: i32,
+    /// Health the player started with, which healing cannot go above.
+    pub max_health: i32,
     speed: f32,
+    deterrent: Deterrent,
+    /// Time the deterrent was last used.
+    deterred_at: Option<f32>,
+}
+
+/// Sent when a player shoos away pest birds around them.
+#[derive(Debug, Event)]
+pub struct DeterEvent {
+    pub position: Vec2,
+    pub radius: f32,
 }
 
 #[derive(Debug, Component)]
@@ -46,6 +63,7 @@ struct PlayerControls {
     move_down: KeyCode,
     sprint: KeyCode,
     fire: KeyCode,
+    deter: KeyCode,
 }
 
 fn setup_player_sys(
@@ -62,21 +80,30 @@ fn setup_player_sys(
                 let player_count: usize = 1; //TODO get this from game state
 
                 for (player_index, player) in level.players[0..player_count].iter().enumerate() {
-                    cmd.entity(*root).with_child((
-                        AssetHandle::<PlayerAsset>(asset_server.load(player.asset.clone())),
-                        PlayerName(format!("Player {}", player_index + 1)),
-                        PlayerControls {
-                            move_left: KeyCode::KeyA,
-                            move_right: KeyCode::KeyD,
-                            move_up: KeyCode::KeyW,
-                            move_down: KeyCode::KeyS,
-                            sprint: KeyCode::ShiftLeft,
-                            fire: KeyCode::Space,
-                        },
-                        Transform::from_translation(player.initial_position),
-                        Collider::Rectangle(Rectangle::new(100., 100.)),
-                        ColliderIntersectionMode::None,
-                    ));
+                    cmd.entity(*root).with_children(|builder| {
+                        let mut player_cmds = builder.spawn((
+                            AssetHandle::<PlayerAsset>(asset_server.load(player.asset.clone())),
+                            PlayerName(format!("Player {}", player_index + 1)),
+                            PlayerControls {
+                                move_left: KeyCode::KeyA,
+                                move_right: KeyCode::KeyD,
+                                move_up: KeyCode::KeyW,
+                                move_down: KeyCode::KeyS,
+                                sprint: KeyCode::ShiftLeft,
+                                fire: KeyCode::Space,
+                                deter: KeyCode::KeyE,
+                            },
+                            Transform::from_translation(player.initial_position),
+                            Collider::Rectangle(Rectangle::new(100., 100.)),
+                            ColliderIntersectionMode::None,
+                        ));
+
+                        if let Some(band) = &player.y_sort {
+                            player_cmds.insert(
+                                YSort::new(band.clone()).with_offset(-PLAYER_SPRITE_SIZE * 0.5),
+                            );
+                        }
+                    });
                 }
             }
             _ => (),
@@ -85,10 +112,55 @@ fn setup_player_sys(
 }
 
 #[derive(Asset, TypePath, Debug, Deserialize, Default)]
-struct PlayerAsset {
+pub(crate) struct PlayerAsset {
     sprite: PathBuf,
     speed: f32,
     health: i32,
+    /// Foods the player can throw, in the order they are switched between.
+    #[serde(default = "all_foods")]
+    foods: Vec<Food>,
+    #[serde(default)]
+    deterrent: Deterrent,
+}
+
+fn all_foods() -> Vec<Food> {
+    Food::ALL.to_vec()
+}
+
+/// Shoos away pest birds close to the player.
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct Deterrent {
+    pub radius: f32,
+    /// Seconds before the deterrent can be used again.
+    pub cooldown: f32,
+}
+
+impl Default for Deterrent {
+    fn default() -> Self {
+        Self { radius: 250., cooldown: 3. }
+    }
+}
+
+impl Validate for PlayerAsset {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        ctx.check_asset_exists(&self.sprite);
+        if self.foods.is_empty() {
+            ctx.error("foods", "player must have at least one food");
+        }
+        if self.speed < 0. {
+            ctx.error("speed", "speed must not be negative");
+        }
+        if self.health <= 0 {
+            ctx.error("health", format!("health must be greater than 0, found {}", self.health));
+        }
+        if self.deterrent.radius <= 0. {
+            ctx.error("radius", "deterrent radius must be positive");
+        }
+        if self.deterrent.cooldown < 0. {
+            ctx.error("cooldown", "deterrent cooldown must not be negative");
+        }
+    }
 }
 
 fn on_player_asset_ready_sys(
@@ -101,11 +173,24 @@ fn on_player_asset_ready_sys(
         let asset = assets.get(asset_id).expect("Asset should exist");
         for entity in entities {
             cmd.entity(*entity).insert((
-                Player { health: asset.health, speed: asset.speed },
-                ProjectileLauncher { launch_key: KeyCode::Space },
+                Player {
+                    health: asset.health,
+                    max_health: asset.health,
+                    speed: asset.speed,
+                    deterrent: asset.deterrent,
+                    deterred_at: None,
+                },
+                ProjectileLauncher {
+                    launch_key: KeyCode::Space,
+                    switch_key: KeyCode::KeyQ,
+                    foods: asset.foods.clone(),
+                    selected: 0,
+                    launched_at: None,
+                },
+                StatusEffects::default(),
                 Sprite {
                     image: asset_server.load(asset.sprite.clone()),
-                    custom_size: Some(Vec2::splat(75.)),
+                    custom_size: Some(Vec2::splat(PLAYER_SPRITE_SIZE)),
                     image_mode: SpriteImageMode::Auto,
                     flip_y: false,
                     ..default()
@@ -116,13 +201,13 @@ fn on_player_asset_ready_sys(
 }
 
 fn player_move_sys(
-    mut players: Query<(&mut Transform, &Player, &PlayerControls)>,
+    mut players: Query<(&mut Transform, &Player, &PlayerControls, &StatusEffects)>,
     keys: Res<ButtonInput<KeyCode>>,
 ) {
-    for (mut player_tf, player, controls) in players.iter_mut() {
+    for (mut player_tf, player, controls, status) in players.iter_mut() {
         let is_sprinting = keys.pressed(controls.sprint);
-        let move_distance =
-            if is_sprinting { player.speed * PLAYER_SPRINT_MULTIPLIER } else { player.speed };
+        let speed = player.speed * status.speed_multiplier();
+        let move_distance = if is_sprinting { speed * PLAYER_SPRINT_MULTIPLIER } else { speed };
 
         if keys.pressed(controls.move_left) {
             player_tf.translation.x -= move_distance
@@ -138,3 +223,24 @@ fn player_move_sys(
         }
     }
 }
+
+/// Uses a player's [Deterrent] when its key is pressed, once the cooldown has passed.
+fn player_deter_sys(
+    mut players: Query<(&Transform, &mut Player, &PlayerControls, &StatusEffects)>,
+    mut deter_evw: EventWriter<DeterEvent>,
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs();
+    for (player_tf, mut player, controls, status) in players.iter_mut() {
+        if !keys.just_pressed(controls.deter) || !status.can_act() {
+            continue;
+        }
+        if player.deterred_at.is_some_and(|at| now - at < player.deterrent.cooldown) {
+            continue;
+        }
+
+        player.deterred_at = Some(now);
+        deter_evw.write(DeterEvent { position: player_tf.translation.xy(), radius: player.deterrent.radius });
+    }
+}
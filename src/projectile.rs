@@ -1,10 +1,21 @@
-use bevy::prelude::*;
+use bevy::{color::palettes::css, platform::collections::HashSet, prelude::*};
+use serde::Deserialize;
 use crate::{
-    level::LevelRootEntity,
-    physics::{Collider, Velocity}
+    bird::{BirdFeedSet, dropping::OnGround},
+    level::{Despawner, LevelRootEntity},
+    physics::{Collider, ColliderContactEvent, Velocity},
+    status::{StatusEffects, StatusKind},
+    util::{GROUND_DEPTH_BAND, YSort},
 };
 use super::GameState;
 
+/// Distance a [Projectile] flies before falling to the ground.
+const PROJECTILE_RANGE: f32 = 700.;
+/// Seconds [GroundFood] lies around before it is gone.
+const GROUND_FOOD_LIFETIME: f32 = 10.;
+/// Angle between the outermost projectiles of a [StatusKind::MultiShot], in degrees.
+const MULTI_SHOT_SPREAD: f32 = 40.;
+
 pub struct ProjectilePlugin;
 
 impl Plugin for ProjectilePlugin {
@@ -12,40 +23,235 @@ impl Plugin for ProjectilePlugin {
         app.add_systems(Update, (
             launch_projectiles_sys,
         ).run_if(in_state(GameState::Game)));
+        app.add_event::<ProjectileMissedEvent>();
+        app.add_systems(
+            FixedUpdate,
+            (
+                // each system stops projectiles the ones before it already dealt with,
+                // so a projectile misses at most once and food a bird ate is not landed
+                (projectile_blocked_sys, projectile_out_of_range_sys, projectile_left_play_area_sys)
+                    .chain()
+                    .after(BirdFeedSet),
+                ground_food_decay_sys,
+            )
+                .run_if(in_state(GameState::Game)),
+        );
     }
 }
 
 #[derive(Component)]
 pub struct Projectile {
-    // payload:
+    pub food: Food,
+    /// Where the projectile was launched from, see [PROJECTILE_RANGE].
+    pub launched_from: Vec2,
+    /// Number of the launch the projectile came from,
+    /// shared by every projectile of a [StatusKind::MultiShot] fan.
+    pub volley: u32,
+}
+
+/// Food lying on the ground after a [Projectile] missed, waiting to be eaten by a bird.
+#[derive(Component)]
+pub struct GroundFood {
+    pub food: Food,
+    /// Elapsed time when the food landed.
+    landed_at: f32,
+}
+
+/// Kinds of food that can be thrown at birds.
+/// How much each feeds a bird depends on the bird, see [crate::bird::asset::BirdAsset::foods].
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Food {
+    #[default]
+    Seeds,
+    Bread,
+    Worms,
+    Berries,
+}
+
+impl Food {
+    pub const ALL: [Food; 4] = [Food::Seeds, Food::Bread, Food::Worms, Food::Berries];
+
+    /// Tint applied to the projectile sprite so foods can be told apart.
+    fn colour(self) -> Color {
+        match self {
+            Food::Seeds => Color::WHITE,
+            Food::Bread => css::BURLYWOOD.into(),
+            Food::Worms => css::HOT_PINK.into(),
+            Food::Berries => css::MEDIUM_PURPLE.into(),
+        }
+    }
+}
+
+/// Sent when a [Projectile] lands, is stopped or leaves the play area without feeding anything.
+#[derive(Debug, Event)]
+pub struct ProjectileMissedEvent {
+    /// [Projectile::volley] of the projectile that missed.
+    pub volley: u32,
 }
 
+/// Marks colliders that stop any [Projectile] that hits them.
+#[derive(Component)]
+pub struct ProjectileBlocker;
+
 #[derive(Component)]
 pub struct ProjectileLauncher {
     pub launch_key: KeyCode,
+    /// Cycles through `foods`.
+    pub switch_key: KeyCode,
+    pub foods: Vec<Food>,
+    pub selected: usize,
+    /// Elapsed time of the last launch, used to pace [StatusKind::RapidFire].
+    pub launched_at: Option<f32>,
+}
+
+impl ProjectileLauncher {
+    /// Food that will be launched next.
+    pub fn food(&self) -> Food {
+        self.foods.get(self.selected).copied().unwrap_or_default()
+    }
 }
 
+/// Launches the selected food when the launch key is pressed, unless the launcher is [StatusKind::Stunned].
+///
+/// Launchers with [StatusKind::RapidFire] keep launching while the key is held,
+/// and those with [StatusKind::MultiShot] launch a fan of projectiles at once.
 fn launch_projectiles_sys(
     mut cmd: Commands,
-    launchers: Query<(&ProjectileLauncher, &Transform)>,
+    mut launchers: Query<(&mut ProjectileLauncher, &Transform, Option<&StatusEffects>)>,
     keys: Res<ButtonInput<KeyCode>>,
     asset_server: Res<AssetServer>,
     root: LevelRootEntity,
+    time: Res<Time>,
+    mut volleys: Local<u32>,
 ) {
-    for (launcher, launcher_tf) in launchers.iter() {
-        if keys.just_pressed(launcher.launch_key) {
+    let now = time.elapsed_secs();
+    for (mut launcher, launcher_tf, status) in launchers.iter_mut() {
+        if keys.just_pressed(launcher.switch_key) && !launcher.foods.is_empty() {
+            launcher.selected = (launcher.selected + 1) % launcher.foods.len();
+            info!("Switched food to {:?}", launcher.food());
+        }
+
+        if status.is_some_and(|status| !status.can_act()) {
+            continue;
+        }
+
+        let rapid_fire = status
+            .and_then(|status| status.magnitude(StatusKind::RapidFire))
+            .filter(|rate| *rate > 0.)
+            .is_some_and(|rate| {
+                keys.pressed(launcher.launch_key)
+                    && launcher.launched_at.is_none_or(|at| now - at >= 1. / rate)
+            });
+        if !keys.just_pressed(launcher.launch_key) && !rapid_fire {
+            continue;
+        }
+        launcher.launched_at = Some(now);
+        *volleys += 1;
+
+        let food = launcher.food();
+        let count = status
+            .and_then(|status| status.magnitude(StatusKind::MultiShot))
+            .map_or(1, |count| (count.round() as u32).max(1));
+        let spread = MULTI_SHOT_SPREAD.to_radians();
+        for i in 0..count {
+            let s = if count > 1 { i as f32 / (count - 1) as f32 - 0.5 } else { 0. };
+            let mut projectile_tf = *launcher_tf;
+            projectile_tf.rotate_z(s * spread);
             cmd.entity(*root).with_child((
-                Projectile {},
+                Projectile { food, launched_from: launcher_tf.translation.xy(), volley: *volleys },
                 Velocity(200.),
                 Collider::Rectangle(Rectangle::new(100., 10.)),
-                launcher_tf.clone(),
+                projectile_tf,
                 Sprite {
                     image: asset_server.load("sprites/seeds.png"),
                     custom_size: Some(Vec2::splat(32.)),
                     image_mode: SpriteImageMode::Auto,
+                    color: food.colour(),
                     ..default()
                 }
             ));
         }
     }
 }
+
+/// Turns a [Projectile] that missed into [GroundFood] where it is.
+///
+/// The projectile may be despawned before the commands run, such as by reaching a [Despawner].
+fn land_projectile(cmd: &mut Commands, projectile: Entity, food: Food, now: f32) {
+    cmd.entity(projectile).try_remove::<(Projectile, Collider, Velocity)>().try_insert((
+        GroundFood { food, landed_at: now },
+        OnGround,
+        YSort::new(GROUND_DEPTH_BAND),
+    ));
+}
+
+/// Drops projectiles that hit a [ProjectileBlocker] to the ground.
+fn projectile_blocked_sys(
+    mut cmd: Commands,
+    mut contact_ev: EventReader<ColliderContactEvent>,
+    projectiles: Query<&Projectile>,
+    blockers: Query<&ProjectileBlocker>,
+    mut missed_evw: EventWriter<ProjectileMissedEvent>,
+    time: Res<Time>,
+) {
+    // a projectile can touch several blockers at once
+    let mut landed = HashSet::new();
+    for contact in contact_ev.read() {
+        if let Some((entity, _)) = contact.between(&projectiles, &blockers)
+            && let Ok(projectile) = projectiles.get(entity)
+            && landed.insert(entity)
+        {
+            land_projectile(&mut cmd, entity, projectile.food, time.elapsed_secs());
+            missed_evw.write(ProjectileMissedEvent { volley: projectile.volley });
+        }
+    }
+}
+
+/// Drops projectiles to the ground once they have flown [PROJECTILE_RANGE].
+fn projectile_out_of_range_sys(
+    mut cmd: Commands,
+    projectiles: Query<(Entity, &Projectile, &Transform)>,
+    mut missed_evw: EventWriter<ProjectileMissedEvent>,
+    time: Res<Time>,
+) {
+    for (entity, projectile, tf) in projectiles.iter() {
+        if tf.translation.xy().distance(projectile.launched_from) >= PROJECTILE_RANGE {
+            land_projectile(&mut cmd, entity, projectile.food, time.elapsed_secs());
+            missed_evw.write(ProjectileMissedEvent { volley: projectile.volley });
+        }
+    }
+}
+
+/// Reports projectiles reaching a [Despawner], which clears them up itself.
+fn projectile_left_play_area_sys(
+    mut contact_ev: EventReader<ColliderContactEvent>,
+    projectiles: Query<&Projectile>,
+    despawners: Query<&Despawner>,
+    mut missed_evw: EventWriter<ProjectileMissedEvent>,
+) {
+    let mut missed = HashSet::new();
+    for contact in contact_ev.read() {
+        if let Some((entity, _)) = contact.between(&projectiles, &despawners)
+            && let Ok(projectile) = projectiles.get(entity)
+            && missed.insert(entity)
+        {
+            missed_evw.write(ProjectileMissedEvent { volley: projectile.volley });
+        }
+    }
+}
+
+/// Fades [GroundFood] out over [GROUND_FOOD_LIFETIME], removing it once it is gone.
+fn ground_food_decay_sys(
+    mut cmd: Commands,
+    mut ground_food: Query<(Entity, &GroundFood, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, food, mut sprite) in ground_food.iter_mut() {
+        let remaining = 1. - (time.elapsed_secs() - food.landed_at) / GROUND_FOOD_LIFETIME;
+        if remaining <= 0. {
+            cmd.entity(entity).despawn();
+        } else {
+            sprite.color.set_alpha(remaining);
+        }
+    }
+}
@@ -0,0 +1,379 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    bird::departure::BirdDeparted,
+    level::{Level, LevelEvent},
+    projectile::ProjectileMissedEvent,
+};
+
+/// Hits in a row needed for each step up of the streak multiplier.
+const STREAK_STEP: u32 = 5;
+/// Multiplier gained for each [STREAK_STEP].
+const STREAK_STEP_MULTIPLIER: f32 = 0.5;
+const MAX_STREAK_MULTIPLIER: f32 = 4.;
+/// Points for feeding a bird until it is no longer hungry.
+const FULLY_FED_BONUS: i32 = 50;
+/// Seconds after one feed within which another continues the chain.
+const CHAIN_WINDOW: f32 = 1.5;
+/// Points for each feed in a chain after the first.
+const CHAIN_BONUS: i32 = 10;
+const MISS_PENALTY: i32 = 5;
+/// Number of recent [ScoreEvent]s listed on the HUD.
+const HUD_RECENT_EVENTS: usize = 3;
+
+/// Turns feeds and misses into points.
+///
+/// Points are reported through [ScoreEvent]s, which are then added to [Level::score].
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FeedEvent>();
+        app.add_event::<ScoreEvent>();
+        app.init_resource::<ScoreTracker>();
+        app.add_systems(OnEnter(GameState::Game), setup_score_hud_sys);
+        app.add_systems(
+            FixedUpdate,
+            (reset_score_sys, score_feed_sys, score_miss_sys, score_departure_sys, apply_score_sys)
+                .chain()
+                .run_if(in_state(GameState::Game)),
+        );
+        app.add_systems(Update, update_score_hud_sys.run_if(in_state(GameState::Game)));
+    }
+}
+
+/// Sent when a bird has been fed.
+#[derive(Debug, Event)]
+pub struct FeedEvent {
+    /// Points the bird gives for the food, before any multiplier.
+    pub points: u32,
+    /// Whether this feed left the bird no longer hungry.
+    pub fully_fed: bool,
+}
+
+/// Why points were given or taken away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScoreReason {
+    Fed,
+    FullyFed,
+    Chain,
+    Miss,
+    /// A bird left while still hungry.
+    Escaped,
+    /// A pest was fed.
+    Pest,
+    /// A dropping giving points was picked up.
+    PowerUp,
+}
+
+impl ScoreReason {
+    pub const ALL: [ScoreReason; 7] = [
+        ScoreReason::Fed,
+        ScoreReason::FullyFed,
+        ScoreReason::Chain,
+        ScoreReason::Miss,
+        ScoreReason::Escaped,
+        ScoreReason::Pest,
+        ScoreReason::PowerUp,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScoreReason::Fed => "Fed",
+            ScoreReason::FullyFed => "Fully fed",
+            ScoreReason::Chain => "Chain",
+            ScoreReason::Miss => "Miss",
+            ScoreReason::Escaped => "Escaped",
+            ScoreReason::Pest => "Fed a pest",
+            ScoreReason::PowerUp => "Power-up",
+        }
+    }
+}
+
+/// Points given or taken away, sent for every change to [Level::score].
+#[derive(Debug, Clone, Event)]
+pub struct ScoreEvent {
+    pub reason: ScoreReason,
+    /// Points before the multiplier was applied.
+    pub base: i32,
+    pub multiplier: f32,
+}
+
+impl ScoreEvent {
+    /// Points after the multiplier has been applied.
+    pub fn points(&self) -> i32 {
+        (self.base as f32 * self.multiplier).round() as i32
+    }
+}
+
+/// Running state of the current level's scoring.
+#[derive(Debug, Default, Resource)]
+pub struct ScoreTracker {
+    /// Feeds since the last miss.
+    pub streak: u32,
+    /// Feeds within [CHAIN_WINDOW] of each other.
+    pub chain: u32,
+    last_feed_at: Option<f32>,
+    /// Total points given for each reason.
+    pub breakdown: HashMap<ScoreReason, i32>,
+    /// Birds that left with no hunger remaining.
+    pub birds_fed: u32,
+    /// Birds that left while still hungry.
+    pub birds_escaped: u32,
+    recent: Vec<ScoreEvent>,
+    /// [crate::projectile::Projectile::volley]s already counted as a miss.
+    missed_volleys: HashSet<u32>,
+}
+
+impl ScoreTracker {
+    /// Multiplier applied to feeds for the current streak.
+    pub fn multiplier(&self) -> f32 {
+        (1. + (self.streak / STREAK_STEP) as f32 * STREAK_STEP_MULTIPLIER).min(MAX_STREAK_MULTIPLIER)
+    }
+
+    /// Total points for `reason` so far.
+    pub fn total(&self, reason: ScoreReason) -> i32 {
+        self.breakdown.get(&reason).copied().unwrap_or_default()
+    }
+
+    /// Counts a feed at elapsed time `now`, continuing the chain if it is within [CHAIN_WINDOW] of the last.
+    fn record_feed(&mut self, now: f32) {
+        self.streak += 1;
+        self.chain = match self.last_feed_at {
+            Some(last) if now - last <= CHAIN_WINDOW => self.chain + 1,
+            _ => 1,
+        };
+        self.last_feed_at = Some(now);
+    }
+
+    /// Ends any streak or chain, unless `volley` has already missed.
+    /// Returns whether this was the volley's first miss.
+    fn record_miss(&mut self, volley: u32) -> bool {
+        if !self.missed_volleys.insert(volley) {
+            return false;
+        }
+        self.streak = 0;
+        self.chain = 0;
+        self.last_feed_at = None;
+        true
+    }
+}
+
+fn reset_score_sys(mut level_evr: EventReader<LevelEvent>, mut tracker: ResMut<ScoreTracker>) {
+    for evt in level_evr.read() {
+        if let LevelEvent::Loaded { .. } = evt {
+            *tracker = default();
+        }
+    }
+}
+
+/// Scores feeds, applying the streak multiplier and any fully fed or chain bonus.
+fn score_feed_sys(
+    mut feed_evr: EventReader<FeedEvent>,
+    mut score_evw: EventWriter<ScoreEvent>,
+    mut tracker: ResMut<ScoreTracker>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs();
+    for feed in feed_evr.read() {
+        tracker.record_feed(now);
+        let multiplier = tracker.multiplier();
+
+        score_evw.write(ScoreEvent { reason: ScoreReason::Fed, base: feed.points as i32, multiplier });
+        if feed.fully_fed {
+            score_evw.write(ScoreEvent {
+                reason: ScoreReason::FullyFed,
+                base: FULLY_FED_BONUS,
+                multiplier,
+            });
+        }
+        if tracker.chain > 1 {
+            score_evw.write(ScoreEvent {
+                reason: ScoreReason::Chain,
+                base: CHAIN_BONUS * (tracker.chain - 1) as i32,
+                multiplier: 1.,
+            });
+        }
+    }
+}
+
+/// Takes points away for food that missed every bird, ending any streak or chain.
+///
+/// A [crate::status::StatusKind::MultiShot] fan counts as one miss however many of its projectiles miss.
+fn score_miss_sys(
+    mut missed_evr: EventReader<ProjectileMissedEvent>,
+    mut score_evw: EventWriter<ScoreEvent>,
+    mut tracker: ResMut<ScoreTracker>,
+) {
+    for evt in missed_evr.read() {
+        if !tracker.record_miss(evt.volley) {
+            continue;
+        }
+        score_evw.write(ScoreEvent { reason: ScoreReason::Miss, base: -MISS_PENALTY, multiplier: 1. });
+    }
+}
+
+/// Counts how many birds left fed and how many escaped hungry.
+fn score_departure_sys(mut departed_evr: EventReader<BirdDeparted>, mut tracker: ResMut<ScoreTracker>) {
+    for departed in departed_evr.read() {
+        if departed.fed_fully {
+            tracker.birds_fed += 1;
+        }
+        if departed.escaped_hungry {
+            debug!("{} escaped hungry", departed.species);
+            tracker.birds_escaped += 1;
+        }
+    }
+}
+
+fn apply_score_sys(
+    mut score_evr: EventReader<ScoreEvent>,
+    mut tracker: ResMut<ScoreTracker>,
+    mut level: ResMut<Level>,
+) {
+    for evt in score_evr.read() {
+        let points = evt.points();
+        level.score = level.score.saturating_add_signed(points);
+        *tracker.breakdown.entry(evt.reason).or_default() += points;
+
+        tracker.recent.push(evt.clone());
+        if tracker.recent.len() > HUD_RECENT_EVENTS {
+            tracker.recent.remove(0);
+        }
+    }
+}
+
+#[derive(Component)]
+struct ScoreHudText;
+
+fn setup_score_hud_sys(mut cmd: Commands, asset_server: Res<AssetServer>) {
+    // Only ever spawn one, as with the bird tweet text.
+    once!(cmd.spawn((
+        ScoreHudText,
+        Text::new(""),
+        TextFont {
+            font: asset_server.load(PathBuf::from("fonts").join("NewHiScore.ttf")),
+            font_size: 30.,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.),
+            left: Val::Px(5.),
+            ..default()
+        },
+    )));
+}
+
+/// Shows the score, current multiplier and where recent points came from.
+fn update_score_hud_sys(
+    mut hud_text: Single<&mut Text, With<ScoreHudText>>,
+    tracker: Res<ScoreTracker>,
+    level: Res<Level>,
+) {
+    if !(tracker.is_changed() || level.is_changed()) {
+        return;
+    }
+
+    let mut text = format!("Score {}  x{:.1}", level.score, tracker.multiplier());
+    for evt in tracker.recent.iter().rev() {
+        text += &format!("\n{:+} {}", evt.points(), evt.reason.label());
+        if evt.multiplier != 1. {
+            text += &format!(" x{:.1}", evt.multiplier);
+        }
+    }
+    hud_text.0 = text;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn multiplier_steps_up_with_the_streak() {
+        let mut tracker = ScoreTracker::default();
+        assert_eq!(tracker.multiplier(), 1.);
+
+        tracker.streak = STREAK_STEP - 1;
+        assert_eq!(tracker.multiplier(), 1.);
+        tracker.streak = STREAK_STEP;
+        assert_eq!(tracker.multiplier(), 1. + STREAK_STEP_MULTIPLIER);
+        tracker.streak = STREAK_STEP * 2 + 1;
+        assert_eq!(tracker.multiplier(), 1. + STREAK_STEP_MULTIPLIER * 2.);
+
+        tracker.streak = STREAK_STEP * 100;
+        assert_eq!(tracker.multiplier(), MAX_STREAK_MULTIPLIER);
+    }
+
+    #[test]
+    fn feeds_build_a_streak_until_a_miss() {
+        let mut tracker = ScoreTracker::default();
+        for i in 0..STREAK_STEP {
+            tracker.record_feed(i as f32 * 10.);
+        }
+        assert_eq!(tracker.streak, STREAK_STEP);
+        assert_eq!(tracker.multiplier(), 1. + STREAK_STEP_MULTIPLIER);
+
+        assert!(tracker.record_miss(1));
+        assert_eq!(tracker.streak, 0);
+        assert_eq!(tracker.multiplier(), 1.);
+    }
+
+    #[test]
+    fn chain_continues_within_the_window() {
+        let mut tracker = ScoreTracker::default();
+        tracker.record_feed(10.);
+        assert_eq!(tracker.chain, 1);
+        tracker.record_feed(10. + CHAIN_WINDOW * 0.5);
+        assert_eq!(tracker.chain, 2);
+        // measured from the last feed rather than the first
+        tracker.record_feed(10. + CHAIN_WINDOW * 1.5);
+        assert_eq!(tracker.chain, 3);
+    }
+
+    #[test]
+    fn chain_restarts_after_the_window_or_a_miss() {
+        let mut tracker = ScoreTracker::default();
+        tracker.record_feed(10.);
+        tracker.record_feed(10. + CHAIN_WINDOW + 0.1);
+        assert_eq!(tracker.chain, 1);
+
+        tracker.record_feed(10. + CHAIN_WINDOW + 0.2);
+        assert_eq!(tracker.chain, 2);
+        assert!(tracker.record_miss(1));
+        assert_eq!(tracker.chain, 0);
+        tracker.record_feed(10. + CHAIN_WINDOW + 0.3);
+        assert_eq!(tracker.chain, 1);
+    }
+
+    #[test]
+    fn volley_misses_once() {
+        let mut world = World::new();
+        world.init_resource::<ScoreTracker>();
+        world.init_resource::<Events<ProjectileMissedEvent>>();
+        world.init_resource::<Events<ScoreEvent>>();
+        world.resource_mut::<ScoreTracker>().streak = 3;
+        // a fan of three projectiles from volley 1 and a single one from volley 2
+        world.send_event_batch([1, 1, 2, 1].map(|volley| ProjectileMissedEvent { volley }));
+
+        world.run_system_once(score_miss_sys).unwrap();
+        let misses: Vec<i32> =
+            world.resource_mut::<Events<ScoreEvent>>().drain().map(|evt| evt.points()).collect();
+        assert_eq!(misses, vec![-MISS_PENALTY, -MISS_PENALTY]);
+
+        // a projectile from the same volley missing later still does not count again
+        world.resource_mut::<ScoreTracker>().streak = 3;
+        world.send_event(ProjectileMissedEvent { volley: 1 });
+        world.run_system_once(score_miss_sys).unwrap();
+        assert!(world.resource::<Events<ScoreEvent>>().is_empty());
+        assert_eq!(world.resource::<ScoreTracker>().streak, 3);
+    }
+}
@@ -0,0 +1,375 @@
+use std::path::PathBuf;
+
+use bevy::{color::palettes::css, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    GameState,
+    player::Player,
+    validate::{Validate, ValidationContext},
+};
+
+/// Size of each icon on the status HUD.
+const HUD_ICON_SIZE: f32 = 48.;
+
+/// Timed, stackable effects on players and birds, see [StatusEffects].
+///
+/// Effects change how their entity behaves for as long as they last,
+/// each system they affect reading them from the entity's [StatusEffects].
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Game), setup_status_hud_sys);
+        app.add_systems(
+            Update,
+            (tick_status_effects_sys, update_status_hud_sys)
+                .chain()
+                .run_if(in_state(GameState::Game)),
+        );
+    }
+}
+
+/// Kinds of temporary effect, each changing how its entity behaves while it lasts.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusKind {
+    /// Multiplies speed by the magnitude for each stack, which should be below 1.
+    Slowed,
+    /// Multiplies speed by the magnitude for each stack, which should be above 1.
+    Hasted,
+    /// Cannot move or act. Players cannot launch or use their deterrent, and birds refuse food and drop nothing.
+    Stunned,
+    /// Stuck in place, but can still act.
+    Sticky,
+    /// Takes no damage.
+    Invulnerable,
+    /// Grows a bird's anger by the magnitude each second for each stack, see [crate::bird::temper::BirdTemper].
+    Enraged,
+    /// Keeps launching while the launch key is held, the magnitude being launches per second.
+    RapidFire,
+    /// Launches a fan of projectiles at once, the magnitude being how many.
+    MultiShot,
+}
+
+impl StatusKind {
+    /// Most times the effect can be stacked by applying it again while it lasts.
+    pub fn max_stacks(self) -> u32 {
+        match self {
+            StatusKind::Slowed | StatusKind::Hasted | StatusKind::Enraged => 3,
+            _ => 1,
+        }
+    }
+
+    /// Short name shown on the HUD.
+    fn label(self) -> &'static str {
+        match self {
+            StatusKind::Slowed => "Slow",
+            StatusKind::Hasted => "Haste",
+            StatusKind::Stunned => "Stun",
+            StatusKind::Sticky => "Stuck",
+            StatusKind::Invulnerable => "Shield",
+            StatusKind::Enraged => "Rage",
+            StatusKind::RapidFire => "Rapid",
+            StatusKind::MultiShot => "Multi",
+        }
+    }
+
+    /// Colour of the effect's HUD icon.
+    fn colour(self) -> Color {
+        match self {
+            StatusKind::Slowed => css::STEEL_BLUE.into(),
+            StatusKind::Hasted => css::GOLD.into(),
+            StatusKind::Stunned => css::SLATE_GRAY.into(),
+            StatusKind::Sticky => css::SADDLE_BROWN.into(),
+            StatusKind::Invulnerable => css::DEEP_SKY_BLUE.into(),
+            StatusKind::Enraged => css::CRIMSON.into(),
+            StatusKind::RapidFire => css::ORANGE.into(),
+            StatusKind::MultiShot => css::MEDIUM_SEA_GREEN.into(),
+        }
+    }
+}
+
+/// A status effect to apply, as given in assets.
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct StatusSpec {
+    pub kind: StatusKind,
+    #[serde(default = "default_magnitude")]
+    pub magnitude: f32,
+    /// Seconds the effect lasts.
+    pub duration: f32,
+}
+
+fn default_magnitude() -> f32 {
+    1.
+}
+
+impl Validate for StatusSpec {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        if self.magnitude < 0. {
+            ctx.error("magnitude", "magnitude must not be negative");
+        }
+        if self.duration <= 0. {
+            ctx.error("duration", format!("duration must be positive for {:?}", self.kind));
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// Strength of each stack.
+    pub magnitude: f32,
+    pub stacks: u32,
+    /// Seconds until the effect runs out.
+    pub remaining: f32,
+}
+
+/// Timed effects currently applied to an entity.
+#[derive(Debug, Default, Component)]
+pub struct StatusEffects(Vec<StatusEffect>);
+
+impl StatusEffects {
+    /// Applies an effect for `duration` seconds.
+    ///
+    /// Applying a kind that is already active adds a stack, up to [StatusKind::max_stacks],
+    /// and refreshes it, keeping the larger magnitude and duration.
+    pub fn apply(&mut self, kind: StatusKind, magnitude: f32, duration: f32) {
+        match self.0.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => {
+                effect.stacks = (effect.stacks + 1).min(kind.max_stacks());
+                effect.magnitude = effect.magnitude.max(magnitude);
+                effect.remaining = effect.remaining.max(duration);
+            }
+            None => self.0.push(StatusEffect { kind, magnitude, stacks: 1, remaining: duration }),
+        }
+    }
+
+    pub fn apply_spec(&mut self, spec: &StatusSpec) {
+        self.apply(spec.kind, spec.magnitude, spec.duration);
+    }
+
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.0.iter().find(|effect| effect.kind == kind)
+    }
+
+    /// Magnitude of each stack of the effect of `kind`, if it is active.
+    pub fn magnitude(&self, kind: StatusKind) -> Option<f32> {
+        self.get(kind).map(|effect| effect.magnitude)
+    }
+
+    /// Magnitude of the effect of `kind` multiplied by its stacks, or `0` if it is not active.
+    pub fn total(&self, kind: StatusKind) -> f32 {
+        self.get(kind).map_or(0., |effect| effect.magnitude * effect.stacks as f32)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    /// Whether the entity can act, such as launching food or eating.
+    pub fn can_act(&self) -> bool {
+        !self.has(StatusKind::Stunned)
+    }
+
+    /// Multiplier for the entity's movement speed, `0` while it is stuck or stunned.
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.has(StatusKind::Stunned) || self.has(StatusKind::Sticky) {
+            return 0.;
+        }
+        [StatusKind::Slowed, StatusKind::Hasted]
+            .into_iter()
+            .filter_map(|kind| self.get(kind))
+            .map(|effect| effect.magnitude.powi(effect.stacks as i32))
+            .product()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.0.iter()
+    }
+}
+
+fn tick_status_effects_sys(
+    mut entities: Query<(Entity, &mut StatusEffects)>,
+    time: Res<Time>,
+) {
+    for (entity, mut effects) in entities.iter_mut() {
+        if effects.0.is_empty() {
+            continue;
+        }
+        for effect in effects.0.iter_mut() {
+            effect.remaining -= time.delta_secs();
+        }
+        effects.0.retain(|effect| {
+            if effect.remaining <= 0. {
+                debug!("{:?} wore off {}", effect.kind, entity);
+            }
+            effect.remaining > 0.
+        });
+    }
+}
+
+/// Row of icons along the bottom of the screen showing the effects on the player.
+#[derive(Component)]
+struct StatusHud {
+    font: TextFont,
+}
+
+/// Icon on the [StatusHud] for one effect, respawned whenever the effects or their stacks change.
+#[derive(Component)]
+struct StatusHudIcon {
+    kind: StatusKind,
+    stacks: u32,
+}
+
+/// Text on a [StatusHudIcon] showing the seconds its effect has left, updated in place.
+#[derive(Component)]
+struct StatusHudTimer(StatusKind);
+
+fn setup_status_hud_sys(mut cmd: Commands, asset_server: Res<AssetServer>) {
+    // Only ever spawn one, as with the score HUD.
+    once!(cmd.spawn((
+        StatusHud {
+            font: TextFont {
+                font: asset_server.load(PathBuf::from("fonts").join("NewHiScore.ttf")),
+                font_size: 14.,
+                ..default()
+            },
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(5.),
+            left: Val::Px(5.),
+            column_gap: Val::Px(5.),
+            ..default()
+        },
+    )));
+}
+
+/// Keeps the status HUD showing the player's effects, each icon showing the effect,
+/// its stacks and the seconds it has left.
+///
+/// Icons are only respawned when effects start, stop or stack, otherwise just their timers are updated.
+fn update_status_hud_sys(
+    mut cmd: Commands,
+    hud: Single<(Entity, &StatusHud, Option<&Children>)>,
+    icons: Query<&StatusHudIcon>,
+    mut timers: Query<(&StatusHudTimer, &mut Text)>,
+    players: Query<&StatusEffects, With<Player>>,
+) {
+    let empty = StatusEffects::default();
+    let effects = players.iter().next().unwrap_or(&empty);
+    let (hud_entity, hud, children) = *hud;
+
+    let shown = children
+        .into_iter()
+        .flatten()
+        .filter_map(|child| icons.get(*child).ok())
+        .map(|icon| (icon.kind, icon.stacks));
+    if shown.eq(effects.iter().map(|effect| (effect.kind, effect.stacks))) {
+        for (timer, mut text) in timers.iter_mut() {
+            if let Some(effect) = effects.get(timer.0) {
+                let remaining = remaining_label(effect);
+                if text.0 != remaining {
+                    text.0 = remaining;
+                }
+            }
+        }
+        return;
+    }
+
+    cmd.entity(hud_entity).despawn_related::<Children>().with_children(|parent| {
+        for effect in effects.iter() {
+            let stacks = if effect.stacks > 1 { format!(" x{}", effect.stacks) } else { String::new() };
+            parent.spawn((
+                StatusHudIcon { kind: effect.kind, stacks: effect.stacks },
+                Node {
+                    width: Val::Px(HUD_ICON_SIZE),
+                    height: Val::Px(HUD_ICON_SIZE),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(effect.kind.colour()),
+                BorderRadius::all(Val::Px(8.)),
+                children![
+                    (Text::new(format!("{}{}", effect.kind.label(), stacks)), hud.font.clone()),
+                    (StatusHudTimer(effect.kind), Text::new(remaining_label(effect)), hud.font.clone()),
+                ],
+            ));
+        }
+    });
+}
+
+fn remaining_label(effect: &StatusEffect) -> String {
+    format!("{:.0}s", effect.remaining.ceil())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_stacks_up_to_the_maximum() {
+        let mut effects = StatusEffects::default();
+        for _ in 0..5 {
+            effects.apply(StatusKind::Slowed, 0.5, 2.);
+        }
+        assert_eq!(effects.iter().count(), 1);
+        assert_eq!(
+            effects.get(StatusKind::Slowed).unwrap().stacks,
+            StatusKind::Slowed.max_stacks()
+        );
+
+        effects.apply(StatusKind::Stunned, 1., 1.);
+        effects.apply(StatusKind::Stunned, 1., 1.);
+        assert_eq!(effects.get(StatusKind::Stunned).unwrap().stacks, 1);
+    }
+
+    #[test]
+    fn apply_refreshes_keeping_the_larger_magnitude_and_duration() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusKind::Enraged, 0.2, 5.);
+        effects.apply(StatusKind::Enraged, 0.1, 8.);
+        let effect = effects.get(StatusKind::Enraged).unwrap();
+        assert_eq!(effect.magnitude, 0.2);
+        assert_eq!(effect.remaining, 8.);
+
+        effects.apply(StatusKind::Enraged, 0.3, 1.);
+        let effect = effects.get(StatusKind::Enraged).unwrap();
+        assert_eq!(effect.magnitude, 0.3);
+        assert_eq!(effect.remaining, 8.);
+        assert_eq!(effects.total(StatusKind::Enraged), 0.3 * 3.);
+    }
+
+    #[test]
+    fn speed_multiplier_combines_stacks() {
+        let mut effects = StatusEffects::default();
+        assert_eq!(effects.speed_multiplier(), 1.);
+
+        effects.apply(StatusKind::Slowed, 0.5, 5.);
+        assert_eq!(effects.speed_multiplier(), 0.5);
+        effects.apply(StatusKind::Slowed, 0.5, 5.);
+        assert_eq!(effects.speed_multiplier(), 0.25);
+        effects.apply(StatusKind::Hasted, 2., 5.);
+        assert_eq!(effects.speed_multiplier(), 0.5);
+
+        // unrelated effects do not change speed
+        effects.apply(StatusKind::Invulnerable, 1., 5.);
+        assert_eq!(effects.speed_multiplier(), 0.5);
+    }
+
+    #[test]
+    fn stuck_and_stunned_cannot_move() {
+        let mut sticky = StatusEffects::default();
+        sticky.apply(StatusKind::Hasted, 2., 5.);
+        sticky.apply(StatusKind::Sticky, 1., 1.);
+        assert_eq!(sticky.speed_multiplier(), 0.);
+        assert!(sticky.can_act());
+
+        let mut stunned = StatusEffects::default();
+        stunned.apply(StatusKind::Stunned, 1., 1.);
+        assert_eq!(stunned.speed_multiplier(), 0.);
+        assert!(!stunned.can_act());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::bird::asset::BirdAsset;
+use crate::birdpedia::{Birdpedia, BirdpediaAssets};
+use bevy::prelude::*;
+
+use super::*;
+
+const CARD_WIDTH: f32 = 280.;
+const CARD_SPRITE_SIZE: f32 = 64.;
+const CARD_FONT_SIZE: f32 = 20.;
+
+pub struct BirdpediaMenuPlugin;
+
+impl Plugin for BirdpediaMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuState::Birdpedia), birdpedia_setup_sys)
+            .add_systems(OnExit(MenuState::Birdpedia), despawn_entities::<OnBirdpediaScreen>);
+    }
+}
+
+/// Tag Entities with this if they are visible on [MenuState::Birdpedia]
+#[derive(Component)]
+struct OnBirdpediaScreen;
+
+/// Lists every bird with what is known about it.
+/// Birds that have never been seen are shown as silhouettes with their details hidden.
+fn birdpedia_setup_sys(
+    mut cmd: Commands,
+    asset_server: Res<AssetServer>,
+    birdpedia: Res<Birdpedia>,
+    birdpedia_assets: Res<BirdpediaAssets>,
+    bird_assets: Res<Assets<BirdAsset>>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let sub_title_text = (
+        Text::new("Birdpedia"),
+        MenuFont::sub_title_font(&asset_server),
+        TextColor(MENU_TEXT_COLOR),
+        Node {
+            margin: UiRect::all(Val::Px(20.0)),
+            ..default()
+        },
+    );
+    let card_font = TextFont {
+        font: asset_server.load(PathBuf::from("fonts").join("NewHiScore.ttf")),
+        font_size: CARD_FONT_SIZE,
+        ..default()
+    };
+
+    let mut birds: Vec<&BirdAsset> = birdpedia_assets
+        .0
+        .iter()
+        .filter_map(|handle| bird_assets.get(handle))
+        .collect();
+    birds.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let container = MenuContainerNode::spawn(&mut cmd);
+    cmd.entity(container)
+        .insert((OnMenuScreen, OnBirdpediaScreen, BackgroundColor(*MENU_BACKGROUND_COLOR)))
+        .with_children(|parent| {
+            parent.spawn(sub_title_text);
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(Node {
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    max_width: Val::Percent(90.),
+                    ..default()
+                })
+                .with_children(|grid| {
+                    for bird in birds {
+                        let entry = birdpedia.entry(&bird.name);
+
+                        // first frame of the sheet, or the whole image
+                        let image = asset_server.load(bird.sprite.clone());
+                        let mut image_node = match &bird.atlas {
+                            Some(atlas) => ImageNode::from_atlas_image(
+                                image,
+                                TextureAtlas { layout: texture_atlas_layouts.add(atlas.layout()), index: 0 },
+                            ),
+                            None => ImageNode::new(image),
+                        };
+                        if entry.is_none() {
+                            image_node.color = Color::BLACK;
+                        }
+
+                        let (name, details) = match entry {
+                            Some(entry) => (
+                                bird.name.clone(),
+                                format!(
+                                    "{}\nHunger {}  Speed {}  Points {}\nSeen {}  Fully fed {}",
+                                    bird.description,
+                                    bird.hunger,
+                                    bird.velocity,
+                                    bird.on_feed_points,
+                                    entry.seen,
+                                    entry.fully_fed,
+                                ),
+                            ),
+                            None => ("???".to_string(), "Not seen yet".to_string()),
+                        };
+
+                        grid.spawn((
+                            Node {
+                                width: Val::Px(CARD_WIDTH),
+                                margin: UiRect::all(Val::Px(8.)),
+                                padding: UiRect::all(Val::Px(8.)),
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(*BUTTON_DEFAULT_COLOR),
+                            children![
+                                (
+                                    image_node,
+                                    Node {
+                                        width: Val::Px(CARD_SPRITE_SIZE),
+                                        height: Val::Px(CARD_SPRITE_SIZE),
+                                        ..default()
+                                    },
+                                ),
+                                (
+                                    Text::new(name),
+                                    MenuFont::body_font(&asset_server),
+                                    TextColor(MENU_TEXT_COLOR),
+                                ),
+                                (
+                                    Text::new(details),
+                                    card_font.clone(),
+                                    TextColor(MENU_TEXT_COLOR),
+                                    TextLayout::new_with_justify(JustifyText::Center),
+                                ),
+                            ],
+                        ));
+                    }
+                });
+        })
+        .with_children(|mut parent| {
+            ButtonNode::spawn(
+                &mut parent,
+                &asset_server,
+                ButtonAction::Menu(MenuButtonAction::BackToMenu),
+                "Back to Menu".to_string(),
+            );
+        });
+}
@@ -1,5 +1,6 @@
 use crate::level::Level;
 use crate::player::Player;
+use crate::score::{ScoreReason, ScoreTracker};
 use bevy::prelude::*;
 
 use super::*;
@@ -22,7 +23,12 @@ game_over_button_action_sys
 #[derive(Component)]
 struct OnGameOverScreen;
 
-fn game_over_setup_sys(mut cmd: Commands, level: ResMut<Level>, asset_server: Res<AssetServer>) {
+fn game_over_setup_sys(
+    mut cmd: Commands,
+    level: ResMut<Level>,
+    score_tracker: Res<ScoreTracker>,
+    asset_server: Res<AssetServer>,
+) {
     let container = MenuContainerNode::spawn(&mut cmd);
     let game_over_text = (
         Text::new("Game Over"),
@@ -34,6 +40,22 @@ fn game_over_setup_sys(mut cmd: Commands, level: ResMut<Level>, asset_server: Re
         MenuFont::sub_title_font(&asset_server),
         TextColor(MENU_TEXT_COLOR),
     );
+    // where the points came from
+    let mut breakdown = ScoreReason::ALL
+        .iter()
+        .map(|reason| format!("{}: {}", reason.label(), score_tracker.total(*reason)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    breakdown += &format!(
+        "\nBirds fed: {}  Escaped: {}",
+        score_tracker.birds_fed, score_tracker.birds_escaped
+    );
+    let breakdown_text = (
+        Text::new(breakdown),
+        MenuFont::body_font(&asset_server),
+        TextColor(MENU_TEXT_COLOR),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
     cmd.entity(container)
         .insert((OnGameOverScreen, BackgroundColor(*MENU_BACKGROUND_COLOR)))
         .with_children(|parent| {
@@ -42,6 +64,9 @@ fn game_over_setup_sys(mut cmd: Commands, level: ResMut<Level>, asset_server: Re
         .with_children(|parent| {
             parent.spawn(score_text);
         })
+        .with_children(|parent| {
+            parent.spawn(breakdown_text);
+        })
         .with_children(|mut parent| {
             ButtonNode::spawn(
                 &mut parent,
@@ -34,6 +34,7 @@ struct OnMainMenuScreen;
 pub enum MenuState {
     MainMenu,
     Settings,
+    Birdpedia,
     #[default]
     Disabled,
 }
@@ -109,6 +110,14 @@ fn main_menu_setup_sys(mut cmd: Commands, asset_server: Res<AssetServer>) {
                 "New Game".to_string(),
             );
         })
+        .with_children(|mut parent| {
+            ButtonNode::spawn(
+                &mut parent,
+                &asset_server,
+                ButtonAction::Menu(MenuButtonAction::Birdpedia),
+                "Birdpedia".to_string(),
+            );
+        })
         .with_children(|mut parent| {
             ButtonNode::spawn(
                 &mut parent,
@@ -155,6 +164,10 @@ fn menu_button_action_sys(
                     menu_state.set(MenuState::MainMenu);
                     debug!("menu state: main menu")
                 }
+                ButtonAction::Menu(MenuButtonAction::Birdpedia) => {
+                    menu_state.set(MenuState::Birdpedia);
+                    debug!("menu state: birdpedia")
+                }
                 ButtonAction::Menu(MenuButtonAction::NewGame) => {
                     game_state.set(GameState::Game);
                     menu_state.set(MenuState::Disabled);
@@ -1,3 +1,4 @@
+pub mod birdpedia;
 pub mod game_over;
 pub mod main_menu;
 pub mod pause;
@@ -7,6 +8,7 @@ use super::GameState;
 use bevy::prelude::*;
 use std::{path::PathBuf, sync::LazyLock};
 
+use birdpedia::*;
 use game_over::*;
 use main_menu::*;
 use pause::*;
@@ -25,7 +27,7 @@ pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((MenuPlugin, PausePlugin, SplashPlugin, GameOverPlugin));
+        app.add_plugins((MenuPlugin, BirdpediaMenuPlugin, PausePlugin, SplashPlugin, GameOverPlugin));
         app.add_systems(
             Update,
             (pause_menu_listener_sys).run_if(in_state(GameState::Game)),
@@ -66,6 +68,7 @@ pub(crate) enum ButtonAction {
 pub(crate) enum MenuButtonAction {
     BackToMenu,
     NewGame,
+    Birdpedia,
 }
 
 /// Enum of all actions a [Button] on the pause menu should be able to perform
@@ -184,6 +187,14 @@ impl MenuFont {
             ..default()
         }
     }
+
+    pub fn body_font(asset_server: &Res<AssetServer>) -> TextFont {
+        TextFont {
+            font: asset_server.load(PathBuf::from("fonts").join("NewHiScore.ttf")),
+            font_size: 40.,
+            ..default()
+        }
+    }
 }
 
 /// On [Interaction] with any [Button], update the colour of it.
@@ -229,7 +240,7 @@ pub fn pause_menu_listener_sys(
                 MenuState::MainMenu => {
                     debug!("Nothing should happen by pressing Esc here");
                 }
-                MenuState::Settings => {
+                MenuState::Settings | MenuState::Birdpedia => {
                     next_menu_state.set(MenuState::MainMenu);
                     debug!("menu state is now main menu");
                 }
@@ -1,43 +1,233 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
-#[derive(Component)]
-pub struct AnimationIndices {
+use crate::validate::ValidationContext;
+
+/// Frames per second of clips that do not give their own.
+const DEFAULT_FPS: f32 = 4.;
+
+/// Layout of the frames within a sprite sheet.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SpriteAtlas {
+    /// Width and height of each frame in pixels.
+    pub cell_size: UVec2,
+    pub columns: u32,
+    pub rows: u32,
+    /// Gap between frames in pixels.
+    #[serde(default)]
+    pub padding: Option<UVec2>,
+    /// Gap between the top left of the sheet and the first frame in pixels.
+    #[serde(default)]
+    pub offset: Option<UVec2>,
+}
+
+impl SpriteAtlas {
+    pub fn layout(&self) -> TextureAtlasLayout {
+        TextureAtlasLayout::from_grid(self.cell_size, self.columns, self.rows, self.padding, self.offset)
+    }
+
+    pub fn frame_count(&self) -> usize {
+        (self.columns * self.rows) as usize
+    }
+
+    pub fn validate(&self, ctx: &mut ValidationContext) {
+        if self.cell_size.x == 0 || self.cell_size.y == 0 {
+            ctx.error("cell_size", "atlas cell_size must be positive");
+        }
+        if self.columns == 0 || self.rows == 0 {
+            ctx.error("columns", "atlas must have at least one column and row");
+        }
+    }
+}
+
+/// What a clip does once it reaches its last frame.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum AnimationLoopMode {
+    /// Starts again from the first frame.
+    #[default]
+    Loop,
+    /// Stays on the last frame.
+    Once,
+    /// Plays backwards to the first frame, then forwards again.
+    PingPong,
+}
+
+/// A range of frames from a sprite sheet, played as an animation.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct AnimationClip {
     pub first: usize,
     pub last: usize,
+    #[serde(default = "default_fps")]
+    pub fps: f32,
+    #[serde(default)]
+    pub mode: AnimationLoopMode,
 }
 
-#[derive(Component, Deref, DerefMut)]
-pub struct AnimationTimer(pub Timer);
+fn default_fps() -> f32 {
+    DEFAULT_FPS
+}
 
-/// Animates sprites from their sprite sheet.
-/// 1x1 sprite sheets (i.e. static images) are acceptable,
-/// allowing for mixture of static and animated sprites on
-/// the same kind of asset.
-/// 
-/// Relies upon [atlas_dimensions] existing in config file.
-/// If they do not exist, it will default to static image.
-pub fn animate_sys(
-    time: Res<Time>,
-    mut query: Query<(&AnimationIndices, &mut AnimationTimer, &mut Sprite)>,
-) {
-    for (indices, mut timer, mut sprite) in &mut query {
-        timer.tick(time.delta());
-
-        if timer.just_finished() {
-            if let Some(atlas) = &mut sprite.texture_atlas {
-                // static image
-                if indices.first == indices.last {
-                    atlas.index = 1;
-                    continue;
-                }
+impl AnimationClip {
+    /// Loops through `frame_count` frames from the start of the sheet.
+    pub fn all_frames(frame_count: usize) -> Self {
+        Self { first: 0, last: frame_count.saturating_sub(1), fps: DEFAULT_FPS, mode: default() }
+    }
+
+    /// Checks the clip is playable with a sheet of `frame_count` frames.
+    pub fn validate(&self, name: &str, frame_count: usize, ctx: &mut ValidationContext) {
+        if self.first > self.last {
+            ctx.error(name, format!("clip {} starts after its last frame", name));
+        }
+        if self.last >= frame_count {
+            ctx.error(
+                name,
+                format!("clip {} ends on frame {} but the sheet has {} frames", name, self.last, frame_count),
+            );
+        }
+        if self.fps <= 0. {
+            ctx.error(name, format!("clip {} must have a positive fps", name));
+        }
+    }
+}
+
+/// Plays an [AnimationClip] on the [TextureAtlas] of a [Sprite].
+///
+/// Sprites without an atlas are left as they are, allowing for a mixture
+/// of static and animated sprites on the same kind of asset.
+#[derive(Debug, Component)]
+pub struct SpriteAnimation {
+    clip: AnimationClip,
+    timer: Timer,
+    /// Frame within the clip.
+    frame: usize,
+    forward: bool,
+}
+
+impl SpriteAnimation {
+    pub fn new(clip: AnimationClip) -> Self {
+        let timer = Timer::from_seconds(1. / clip.fps.max(f32::EPSILON), TimerMode::Repeating);
+        Self { clip, timer, frame: 0, forward: true }
+    }
+
+    /// Starts playing `clip` from its first frame, unless it is already playing.
+    pub fn play(&mut self, clip: AnimationClip) {
+        if self.clip != clip {
+            *self = Self::new(clip);
+        }
+    }
+
+    /// Index of the current frame within the sprite sheet.
+    pub fn index(&self) -> usize {
+        self.clip.first + self.frame
+    }
 
-                // standard animation loop
-                if atlas.index >= indices.last {
-                    atlas.index = indices.first;
-                } else {
-                    atlas.index += 1;
+    fn advance(&mut self) {
+        let last = self.clip.last.saturating_sub(self.clip.first);
+        if last == 0 {
+            self.frame = 0;
+            return;
+        }
+
+        match self.clip.mode {
+            AnimationLoopMode::Loop => self.frame = if self.frame >= last { 0 } else { self.frame + 1 },
+            AnimationLoopMode::Once => self.frame = (self.frame + 1).min(last),
+            AnimationLoopMode::PingPong => {
+                if self.forward && self.frame >= last {
+                    self.forward = false;
+                } else if !self.forward && self.frame == 0 {
+                    self.forward = true;
                 }
+                self.frame = if self.forward { self.frame + 1 } else { self.frame - 1 };
             }
         }
     }
 }
+
+/// Animates sprites from their sprite sheet.
+pub fn animate_sys(time: Res<Time>, mut query: Query<(&mut SpriteAnimation, &mut Sprite)>) {
+    for (mut animation, mut sprite) in &mut query {
+        animation.timer.tick(time.delta());
+        for _ in 0..animation.timer.times_finished_this_tick() {
+            animation.advance();
+        }
+
+        // also picks up clips that have just been changed
+        let index = animation.index();
+        if sprite.texture_atlas.as_ref().is_none_or(|atlas| atlas.index == index) {
+            continue;
+        }
+        if let Some(atlas) = &mut sprite.texture_atlas {
+            atlas.index = index;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sheet indices shown over `steps` advances of a clip from frame 2 to 5.
+    fn indices(mode: AnimationLoopMode, steps: usize) -> Vec<usize> {
+        let mut animation = SpriteAnimation::new(AnimationClip {
+            first: 2,
+            last: 5,
+            fps: 4.,
+            mode,
+        });
+        let mut indices = vec![animation.index()];
+        for _ in 0..steps {
+            animation.advance();
+            indices.push(animation.index());
+        }
+        indices
+    }
+
+    #[test]
+    fn loop_starts_again_from_the_first_frame() {
+        assert_eq!(
+            indices(AnimationLoopMode::Loop, 9),
+            [2, 3, 4, 5, 2, 3, 4, 5, 2, 3]
+        );
+    }
+
+    #[test]
+    fn ping_pong_plays_back_and_forth() {
+        assert_eq!(
+            indices(AnimationLoopMode::PingPong, 9),
+            [2, 3, 4, 5, 4, 3, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn once_stays_on_the_last_frame() {
+        assert_eq!(indices(AnimationLoopMode::Once, 6), [2, 3, 4, 5, 5, 5, 5]);
+    }
+
+    #[test]
+    fn single_frame_clips_stay_put() {
+        let mut animation = SpriteAnimation::new(AnimationClip {
+            first: 3,
+            last: 3,
+            fps: 4.,
+            mode: AnimationLoopMode::PingPong,
+        });
+        animation.advance();
+        animation.advance();
+        assert_eq!(animation.index(), 3);
+    }
+
+    #[test]
+    fn play_restarts_only_for_a_different_clip() {
+        let clip = AnimationClip::all_frames(4);
+        let mut animation = SpriteAnimation::new(clip.clone());
+        animation.advance();
+        animation.play(clip);
+        assert_eq!(animation.index(), 1);
+
+        animation.play(AnimationClip {
+            first: 2,
+            ..AnimationClip::all_frames(4)
+        });
+        assert_eq!(animation.index(), 2);
+    }
+}
@@ -2,7 +2,9 @@ pub mod ron_asset_loader;
 pub mod asset_manager;
 pub mod transform_interpolation;
 pub mod animation;
+pub mod y_sort;
 
 pub use asset_manager::{ AssetHandle, EntityAssetReadyEvent, AssetManagerPlugin };
 pub use transform_interpolation::{TargetTransform, TransformInterpolationPlugin};
-pub use animation::{ animate_sys, AnimationIndices, AnimationTimer };
+pub use animation::{ animate_sys, AnimationClip, SpriteAnimation, SpriteAtlas };
+pub use y_sort::{DepthBand, DepthBands, GROUND_DEPTH_BAND, YSort, YSortPlugin};
@@ -1,9 +1,16 @@
+use std::path::{Path, PathBuf};
+
 use bevy::{
     asset::{AssetLoader, LoadContext, io::Reader},
     prelude::*,
 };
 use serde::de::DeserializeOwned;
 
+/// Field naming the asset that a RON file inherits from, see [inherit].
+const BASE_FIELD: &str = "base";
+/// Longest chain of bases followed before giving up, in case bases refer back to each other.
+const MAX_BASE_DEPTH: usize = 8;
+
 #[derive(Default)]
 pub struct RonAssetLoader<T> {
     _marker: std::marker::PhantomData<T>,
@@ -21,15 +28,333 @@ where
         &self,
         reader: &mut dyn Reader,
         _settings: &(),
-        _load_context: &mut LoadContext<'_>,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let custom_asset = ron::de::from_bytes::<T>(&bytes)?;
-        Ok(custom_asset)
+        let mut source = String::from_utf8(bytes)?;
+
+        // reading the base also reloads this asset when the base changes
+        for depth in 0.. {
+            let Some(base) = base_path(&source)? else {
+                break;
+            };
+            if depth == MAX_BASE_DEPTH {
+                return Err(too_many_bases().into());
+            }
+            let base_source = String::from_utf8(load_context.read_asset_bytes(base.as_path()).await?)?;
+            source = inherit(&base_source, &source)?;
+        }
+        Ok(ron::de::from_str::<T>(&source)?)
     }
 
     fn extensions(&self) -> &[&str] {
         &["ron"]
     }
 }
+
+/// Resolves the bases of `source` by reading them with `read`, the same way as [RonAssetLoader].
+pub fn resolve_bases(
+    source: &str,
+    mut read: impl FnMut(&Path) -> std::io::Result<String>,
+) -> Result<String, String> {
+    let mut source = source.to_string();
+    for depth in 0.. {
+        let Some(base) = base_path(&source)? else {
+            break;
+        };
+        if depth == MAX_BASE_DEPTH {
+            return Err(too_many_bases());
+        }
+        let base_source =
+            read(&base).map_err(|err| format!("unable to read base `{}`: {}", base.display(), err))?;
+        source = inherit(&base_source, &source)?;
+    }
+    Ok(source)
+}
+
+fn too_many_bases() -> String {
+    format!("more than {} bases, do they refer to each other?", MAX_BASE_DEPTH)
+}
+
+/// Path given by the `base` field of a RON struct, if it has one.
+pub fn base_path(source: &str) -> Result<Option<PathBuf>, String> {
+    let Some(fields) = RonFields::parse(source) else {
+        return Ok(None);
+    };
+    fields
+        .get(BASE_FIELD)
+        .map(|value| ron::de::from_str::<PathBuf>(value).map_err(|err| format!("invalid base: {}", err)))
+        .transpose()
+}
+
+/// Combines the RON struct in `source` with the one in `base_source`.
+///
+/// Fields given by `source` replace those of the base as a whole, so overriding one field
+/// of a nested struct means giving the rest of that struct again. Everything else is taken from the base,
+/// including any base of its own.
+pub fn inherit(base_source: &str, source: &str) -> Result<String, String> {
+    let base = RonFields::parse(base_source).ok_or("base is not a RON struct")?;
+    let overrides = RonFields::parse(source).ok_or("expected a RON struct")?;
+
+    let mut fields: Vec<(&str, &str)> = base
+        .fields
+        .iter()
+        .map(|&(name, value)| {
+            let value = overrides.get(name).filter(|_| name != BASE_FIELD).unwrap_or(value);
+            (name, value)
+        })
+        .collect();
+    for &(name, value) in &overrides.fields {
+        if name != BASE_FIELD && base.get(name).is_none() {
+            fields.push((name, value));
+        }
+    }
+
+    let mut merged = format!("{}(\n", overrides.prefix);
+    for (name, value) in fields {
+        merged += &format!("    {}: {},\n", name, value);
+    }
+    merged += ")\n";
+    Ok(merged)
+}
+
+/// Source text of each top level field of a RON struct.
+struct RonFields<'a> {
+    /// Attributes, comments and the struct name before the opening bracket.
+    prefix: &'a str,
+    fields: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> RonFields<'a> {
+    fn parse(source: &'a str) -> Option<Self> {
+        let mut scanner = Scanner::new(source);
+        let open = loop {
+            let (i, c) = scanner.next()?;
+            if c == '(' && scanner.depth == 1 {
+                break i;
+            }
+        };
+
+        let mut fields = vec![];
+        let mut start = open + 1;
+        let mut colon = None;
+        while let Some((i, c)) = scanner.next() {
+            let end_of_struct = c == ')' && scanner.depth == 0;
+            if scanner.depth == 1 && c == ':' && colon.is_none() {
+                colon = Some(i);
+            } else if (scanner.depth == 1 && c == ',') || end_of_struct {
+                if let Some(colon) = colon {
+                    let name = skip_comments(&source[start..colon]).trim();
+                    let value = source[colon + 1..i].trim();
+                    fields.push((name, value));
+                }
+                start = i + 1;
+                colon = None;
+                if end_of_struct {
+                    return Some(Self { prefix: source[..open].trim_end(), fields });
+                }
+            }
+        }
+        None
+    }
+
+    fn get(&self, name: &str) -> Option<&'a str> {
+        self.fields.iter().find(|(field, _)| *field == name).map(|(_, value)| *value)
+    }
+}
+
+/// Removes whitespace and comments from the start of `text`.
+fn skip_comments(mut text: &str) -> &str {
+    loop {
+        text = text.trim_start();
+        if let Some(rest) = text.strip_prefix("//") {
+            text = rest.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(rest) = text.strip_prefix("/*") {
+            text = rest.split_once("*/").map_or("", |(_, rest)| rest);
+        } else {
+            return text;
+        }
+    }
+}
+
+/// Walks through RON source outside of strings and comments, keeping track of how deeply nested it is.
+struct Scanner<'a> {
+    source: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    /// Number of brackets open after the last character returned.
+    depth: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(source: &'a str) -> Self {
+        Self { source, chars: source.char_indices().peekable(), depth: 0 }
+    }
+}
+
+impl Iterator for Scanner<'_> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (i, c) = self.chars.next()?;
+            match c {
+                '"' => {
+                    while let Some((_, c)) = self.chars.next() {
+                        match c {
+                            '\\' => {
+                                self.chars.next();
+                            }
+                            '"' => break,
+                            _ => (),
+                        }
+                    }
+                }
+                '/' if self.source[i..].starts_with("//") => {
+                    while self.chars.next_if(|&(_, c)| c != '\n').is_some() {}
+                }
+                '/' if self.source[i..].starts_with("/*") => {
+                    let end = self.source[i + 2..].find("*/").map_or(self.source.len(), |end| i + 2 + end + 2);
+                    while self.chars.next_if(|&(j, _)| j < end).is_some() {}
+                }
+                '(' | '[' | '{' => {
+                    self.depth += 1;
+                    return Some((i, c));
+                }
+                ')' | ']' | '}' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    return Some((i, c));
+                }
+                _ => return Some((i, c)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, io};
+
+    use super::*;
+
+    /// Fields of a merged struct, for comparing regardless of layout.
+    fn fields(source: &str) -> Vec<(&str, &str)> {
+        RonFields::parse(source)
+            .expect("merged source is a RON struct")
+            .fields
+    }
+
+    #[test]
+    fn inherit_replaces_nested_structs_whole() {
+        let base = r#"BirdAsset (
+            name: "goose",
+            flocking: Some(( radius: 300, spacing: 90 )),
+            hunger: 3,
+        )"#;
+        let source =
+            r#"( base: "birds/goose.ron", flocking: Some(( radius: 100 )), velocity: 80 )"#;
+
+        let merged = inherit(base, source).unwrap();
+        assert_eq!(
+            fields(&merged),
+            [
+                ("name", "\"goose\""),
+                ("flocking", "Some(( radius: 100 ))"),
+                ("hunger", "3"),
+                ("velocity", "80"),
+            ]
+        );
+    }
+
+    #[test]
+    fn inherit_ignores_brackets_and_commas_in_strings() {
+        let base = r#"( name: "a (big, loud) bird", hunger: 3 )"#;
+        let source = r#"( base: "birds/a.ron", description: "flies off :(" )"#;
+
+        let merged = inherit(base, source).unwrap();
+        assert_eq!(
+            fields(&merged),
+            [
+                ("name", "\"a (big, loud) bird\""),
+                ("hunger", "3"),
+                ("description", "\"flies off :(\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn inherit_ignores_comments() {
+        let base = "BirdAsset (
+            // hunger: 9, (
+            hunger: 3,
+            /* velocity: 1, ) */
+            velocity: 100,
+        )";
+        let source = r#"(
+            base: "birds/a.ron",
+            // the goose is a little slower
+            velocity: 80, // was 100
+        )"#;
+
+        let merged = inherit(base, source).unwrap();
+        assert_eq!(fields(&merged), [("hunger", "3"), ("velocity", "80")]);
+    }
+
+    #[test]
+    fn inherit_keeps_the_base_of_the_base() {
+        let base = r#"( base: "birds/c.ron", hunger: 3 )"#;
+        let source = r#"( base: "birds/b.ron", hunger: 4 )"#;
+
+        let merged = inherit(base, source).unwrap();
+        assert_eq!(
+            base_path(&merged).unwrap(),
+            Some(PathBuf::from("birds/c.ron"))
+        );
+        assert_eq!(
+            fields(&merged),
+            [("base", "\"birds/c.ron\""), ("hunger", "4")]
+        );
+    }
+
+    /// Resolves a file based on a chain of `length` others, each based on the next.
+    fn resolve_chain(length: usize) -> Result<String, String> {
+        let files: HashMap<PathBuf, String> = (0..length)
+            .map(|i| {
+                let source = if i + 1 < length {
+                    format!(r#"( base: "{}.ron", field_{}: {} )"#, i + 1, i, i)
+                } else {
+                    format!("( field_{}: {} )", i, i)
+                };
+                (PathBuf::from(format!("{}.ron", i)), source)
+            })
+            .collect();
+        resolve_bases(r#"( base: "0.ron" )"#, |path| {
+            files
+                .get(path)
+                .cloned()
+                .ok_or(io::Error::from(io::ErrorKind::NotFound))
+        })
+    }
+
+    #[test]
+    fn resolve_bases_follows_chains_up_to_max_depth() {
+        let resolved = resolve_chain(MAX_BASE_DEPTH).unwrap();
+        assert_eq!(base_path(&resolved).unwrap(), None);
+        assert_eq!(fields(&resolved).len(), MAX_BASE_DEPTH);
+
+        assert!(resolve_chain(MAX_BASE_DEPTH + 1).is_err());
+    }
+
+    #[test]
+    fn resolve_bases_gives_up_on_cycles() {
+        let read = |_: &Path| Ok(r#"( base: "self.ron", hunger: 3 )"#.to_string());
+        let err = resolve_bases(r#"( base: "self.ron" )"#, read).unwrap_err();
+        assert!(err.contains("bases"), "{}", err);
+    }
+
+    #[test]
+    fn base_path_is_none_without_a_base() {
+        assert_eq!(base_path("( hunger: 3 )").unwrap(), None);
+        assert!(base_path("( base: 3 )").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+use serde::Deserialize;
+
+/// Band for things lying on the ground, such as droppings.
+pub const GROUND_DEPTH_BAND: &str = "ground";
+
+pub struct YSortPlugin;
+
+impl Plugin for YSortPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DepthBands>();
+        app.add_systems(PostUpdate, y_sort_sys.before(TransformSystem::TransformPropagate));
+    }
+}
+
+/// Derives the Z translation of an entity from its Y translation,
+/// so that entities lower on the screen are drawn in front of those above them.
+///
+/// The Z value is kept within the range of the named [DepthBand].
+/// Entities using a band that does not exist keep their current Z translation.
+#[derive(Debug, Component)]
+pub struct YSort {
+    pub band: String,
+    /// Offset from the translation to the point that is sorted on,
+    /// typically the bottom of the sprite so tall things sort by where they touch the ground.
+    pub offset: f32,
+}
+
+impl YSort {
+    pub fn new(band: impl Into<String>) -> Self {
+        Self { band: band.into(), offset: 0. }
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
+/// Range of Z values that [YSort] entities are placed within.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DepthBand {
+    /// Lowest and highest Z values. The lowest is used at the top of the Y range.
+    pub z: Vec2,
+    /// Lowest and highest Y values expected. Entities outside this are clamped to the ends of the band.
+    pub y: Vec2,
+}
+
+impl DepthBand {
+    fn z_at(&self, y: f32) -> f32 {
+        let s = ((self.y.y - y) / (self.y.y - self.y.x)).clamp(0., 1.);
+        self.z.x + (self.z.y - self.z.x) * s
+    }
+}
+
+/// The [DepthBand]s available to [YSort] entities, by name.
+#[derive(Debug, Default, Resource, Deref, DerefMut)]
+pub struct DepthBands(pub HashMap<String, DepthBand>);
+
+fn y_sort_sys(
+    mut entities: Query<(&mut Transform, Ref<YSort>)>,
+    bands: Res<DepthBands>,
+) {
+    for (mut tf, y_sort) in entities.iter_mut() {
+        if !(tf.is_changed() || y_sort.is_changed() || bands.is_changed()) {
+            continue;
+        }
+
+        if let Some(band) = bands.get(&y_sort.band) {
+            let z = band.z_at(tf.translation.y + y_sort.offset);
+            // avoid flagging the transform as changed every frame
+            if tf.translation.z != z {
+                tf.translation.z = z;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn band() -> DepthBand {
+        DepthBand { z: Vec2::new(10., 20.), y: Vec2::new(-300., 300.) }
+    }
+
+    #[test]
+    fn band_edges_map_to_ends_of_z_range() {
+        assert_eq!(band().z_at(300.), 10.);
+        assert_eq!(band().z_at(-300.), 20.);
+        assert_eq!(band().z_at(0.), 15.);
+    }
+
+    #[test]
+    fn outside_band_is_clamped() {
+        assert_eq!(band().z_at(1000.), 10.);
+        assert_eq!(band().z_at(-1000.), 20.);
+    }
+
+    #[test]
+    fn lower_is_drawn_in_front() {
+        let band = band();
+        let mut last = band.z_at(400.);
+        for y in (-400..400).rev().step_by(25) {
+            let z = band.z_at(y as f32);
+            assert!(z >= last, "z at {} is {}, behind {}", y, z, last);
+            last = z;
+        }
+        assert!(band.z_at(-100.) > band.z_at(100.));
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    bird::{asset::BirdAsset, dropping::BirdDroppingAsset},
+    level::{LevelAsset, prefab::SceneryPrefabLibrary},
+    player::PlayerAsset,
+    util::ron_asset_loader::{base_path, resolve_bases},
+};
+
+/// Checks on a parsed asset that cannot be expressed by its type alone.
+///
+/// Implemented alongside each asset so the checks stay next to the systems
+/// that would otherwise panic or misbehave at runtime.
+pub(crate) trait Validate {
+    fn validate(&self, ctx: &mut ValidationContext);
+}
+
+/// A problem found in an asset file.
+#[derive(Debug)]
+pub(crate) struct ValidationError {
+    file: PathBuf,
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}: {}", self.file.display(), self.line, self.column, self.message)
+    }
+}
+
+/// State for validating a single file.
+///
+/// Collects errors along with any RON assets the file references,
+/// which are validated in turn by [run].
+pub(crate) struct ValidationContext<'a> {
+    asset_root: &'a Path,
+    file: &'a Path,
+    source: &'a str,
+    errors: Vec<ValidationError>,
+    references: Vec<PathBuf>,
+}
+
+impl<'a> ValidationContext<'a> {
+    fn new(asset_root: &'a Path, file: &'a Path, source: &'a str) -> Self {
+        Self { asset_root, file, source, errors: vec![], references: vec![] }
+    }
+
+    /// Records an error positioned at the first occurrence of `near` within the file.
+    ///
+    /// Serde does not keep spans for deserialized values, so searching for the
+    /// field name or value is the best we can do. Falls back to the start of the file.
+    pub fn error(&mut self, near: &str, message: impl Into<String>) {
+        let (line, column) = self
+            .source
+            .find(near)
+            .map_or((1, 1), |offset| line_column(self.source, offset));
+        self.errors.push(ValidationError {
+            file: self.file.to_path_buf(),
+            line,
+            column,
+            message: message.into(),
+        });
+    }
+
+    /// Checks that `path` exists relative to the asset root.
+    pub fn check_asset_exists(&mut self, path: &Path) -> bool {
+        let exists = self.asset_root.join(path).is_file();
+        if !exists {
+            let near = path.to_string_lossy();
+            self.error(&near, format!("referenced asset `{}` does not exist", near));
+        }
+        exists
+    }
+
+    /// Checks that `path` exists and queues the RON file it refers to for validation.
+    pub fn check_ron_asset(&mut self, path: &Path) {
+        if self.check_asset_exists(path) {
+            self.references.push(self.asset_root.join(path));
+        }
+    }
+
+    /// Reads a RON asset referenced by the file being validated.
+    ///
+    /// Returns `None` if it cannot be read or parsed,
+    /// those errors are reported when the referenced file is itself validated.
+    pub fn read_ron_asset<T: DeserializeOwned>(&self, path: &Path) -> Option<T> {
+        let source = fs::read_to_string(self.asset_root.join(path)).ok()?;
+        ron::de::from_str(&self.resolve_bases(&source).ok()?).ok()
+    }
+
+    /// Combines `source` with any base it inherits from, see [crate::util::ron_asset_loader::inherit].
+    fn resolve_bases(&self, source: &str) -> Result<String, String> {
+        resolve_bases(source, |base| fs::read_to_string(self.asset_root.join(base)))
+    }
+
+    /// Checks that a weight used to pick between options is greater than zero.
+    pub fn check_weight(&mut self, weight: f32, near: &str) {
+        if weight.is_nan() || weight <= 0. {
+            self.error(near, format!("probability weight must be positive, found {}", weight));
+        }
+    }
+
+    /// Checks that a value passed to `random_bool` is within `0..=1`.
+    pub fn check_probability(&mut self, probability: f32, near: &str) {
+        if !(0. ..=1.).contains(&probability) {
+            self.error(near, format!("probability must be between 0 and 1, found {}", probability));
+        }
+    }
+}
+
+/// Validates each file, or every `.ron` file within each directory, in `paths`.
+///
+/// Assets referenced by those files are validated as well.
+/// Errors are printed to stderr. Returns the process exit code.
+pub(crate) fn run(paths: &[PathBuf], asset_root: &Path) -> i32 {
+    let mut queue = VecDeque::new();
+    for path in paths {
+        if let Err(err) = collect_ron_files(path, &mut queue) {
+            eprintln!("{}: {}", path.display(), err);
+            return 2;
+        }
+    }
+
+    let mut visited = HashSet::new();
+    let mut error_count = 0;
+    while let Some(file) = queue.pop_front() {
+        if !visited.insert(fs::canonicalize(&file).unwrap_or(file.clone())) {
+            continue;
+        }
+
+        let (errors, references) = validate_file(&file, asset_root);
+        for error in &errors {
+            eprintln!("{}", error);
+        }
+        error_count += errors.len();
+        queue.extend(references);
+    }
+
+    println!("Validated {} file(s), found {} error(s)", visited.len(), error_count);
+    if error_count == 0 { 0 } else { 1 }
+}
+
+fn collect_ron_files(path: &Path, files: &mut VecDeque<PathBuf>) -> std::io::Result<()> {
+    if path.is_dir() {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort();
+        for entry in entries {
+            if entry.is_dir() || entry.extension().is_some_and(|ext| ext == "ron") {
+                collect_ron_files(&entry, files)?;
+            }
+        }
+    } else {
+        fs::metadata(path)?;
+        files.push_back(path.to_path_buf());
+    }
+    Ok(())
+}
+
+fn validate_file(file: &Path, asset_root: &Path) -> (Vec<ValidationError>, Vec<PathBuf>) {
+    let source = match fs::read_to_string(file) {
+        Ok(source) => source,
+        Err(err) => {
+            let mut ctx = ValidationContext::new(asset_root, file, "");
+            ctx.error("", err.to_string());
+            return (ctx.errors, ctx.references);
+        }
+    };
+
+    let mut ctx = ValidationContext::new(asset_root, file, &source);
+    if let Ok(Some(base)) = base_path(&source) {
+        ctx.check_ron_asset(&base);
+    }
+    match asset_kind(file, &source) {
+        Some("LevelAsset") => parse_and_validate::<LevelAsset>(&mut ctx),
+        Some("BirdAsset") => parse_and_validate::<BirdAsset>(&mut ctx),
+        Some("BirdDroppingAsset") => parse_and_validate::<BirdDroppingAsset>(&mut ctx),
+        Some("PlayerAsset") => parse_and_validate::<PlayerAsset>(&mut ctx),
+        Some("SceneryPrefabLibrary") => parse_and_validate::<SceneryPrefabLibrary>(&mut ctx),
+        _ => ctx.error("", "unable to determine asset type"),
+    }
+    (ctx.errors, ctx.references)
+}
+
+fn parse_and_validate<T: DeserializeOwned + Validate>(ctx: &mut ValidationContext) {
+    // Parse the same way as RonAssetLoader so errors match what the game would see.
+    let source = match ctx.resolve_bases(ctx.source) {
+        Ok(source) => source,
+        Err(err) => return ctx.error("base", err),
+    };
+    match ron::de::from_str::<T>(&source) {
+        Ok(asset) => asset.validate(ctx),
+        // positions within a combined file do not match up with the file itself
+        Err(err) if source != ctx.source => {
+            ctx.error("", format!("combined with its base: {}", err.code))
+        }
+        Err(err) => ctx.errors.push(ValidationError {
+            file: ctx.file.to_path_buf(),
+            line: err.position.line,
+            column: err.position.col,
+            message: err.code.to_string(),
+        }),
+    }
+}
+
+/// Determines the type of asset from the struct name at the start of the file,
+/// falling back to the directory it lives in when the name is omitted.
+fn asset_kind<'a>(file: &Path, source: &'a str) -> Option<&'a str> {
+    let mut rest = source.trim_start();
+    loop {
+        if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment.split_once('\n').map_or("", |(_, r)| r).trim_start();
+        } else if let Some(attribute) = rest.strip_prefix("#![") {
+            rest = attribute.split_once(']').map_or("", |(_, r)| r).trim_start();
+        } else {
+            break;
+        }
+    }
+
+    let name_len = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    if name_len > 0 {
+        return Some(&rest[..name_len]);
+    }
+
+    let in_dir = |dir: &str| file.components().any(|c| c.as_os_str() == dir);
+    if in_dir("levels") {
+        Some("LevelAsset")
+    } else if in_dir("droppings") {
+        Some("BirdDroppingAsset")
+    } else if in_dir("birds") {
+        Some("BirdAsset")
+    } else if in_dir("players") {
+        Some("PlayerAsset")
+    } else {
+        None
+    }
+}
+
+/// Converts a byte offset into a one based line and column.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asset_kind_from_struct_name() {
+        let file = Path::new("assets/levels/test.ron");
+        assert_eq!(
+            asset_kind(file, "BirdAsset ( name: \"a\" )"),
+            Some("BirdAsset")
+        );
+        assert_eq!(
+            asset_kind(
+                file,
+                "// a comment\n#![enable(implicit_some)]\n  PlayerAsset(\n)"
+            ),
+            Some("PlayerAsset")
+        );
+    }
+
+    #[test]
+    fn asset_kind_from_directory_without_struct_name() {
+        let kind = |file: &str| asset_kind(Path::new(file), "( name: \"a\" )");
+        assert_eq!(kind("assets/levels/test.ron"), Some("LevelAsset"));
+        assert_eq!(
+            kind("assets/birds/droppings/seeds.ron"),
+            Some("BirdDroppingAsset")
+        );
+        assert_eq!(kind("assets/birds/magpie.ron"), Some("BirdAsset"));
+        assert_eq!(kind("assets/players/player.ron"), Some("PlayerAsset"));
+        assert_eq!(kind("assets/other/thing.ron"), None);
+    }
+
+    #[test]
+    fn line_column_is_one_based() {
+        let source = "(\n    name: \"é\",\n    hunger: x,\n)";
+        assert_eq!(line_column(source, 0), (1, 1));
+        assert_eq!(line_column(source, 1), (1, 2));
+        assert_eq!(line_column(source, 2), (2, 1));
+        // columns count characters rather than bytes
+        let hunger = source.find("hunger").unwrap();
+        assert_eq!(line_column(source, hunger), (3, 5));
+        let comma = source.find("\",").unwrap() + 1;
+        assert_eq!(line_column(source, comma), (2, 14));
+        assert_eq!(line_column(source, source.len()), (4, 2));
+    }
+}